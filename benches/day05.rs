@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/bin/day05.rs"]
+mod day05;
+
+use day05::{DenseFloor, Floor, Line};
+
+/// Pseudo-random horizontal, vertical and diagonal lines of vents, resembling
+/// the size of the puzzle input
+fn lines() -> Vec<Line> {
+    let mut seed: u64 = 42;
+    let mut random = move |n: u64| {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) % n
+    };
+    (0..500)
+        .map(|i| {
+            let (x, y, len) = (random(800) + 100, random(800) + 100, random(100));
+            let (dx, dy) = [(1, 0), (0, 1), (1, 1), (1, -1)][i % 4];
+            let (tx, ty) = (x as i64 + dx * len as i64, y as i64 + dy * len as i64);
+            format!("{x},{y} -> {tx},{ty}").parse().unwrap()
+        })
+        .collect()
+}
+
+fn bench(c: &mut Criterion) {
+    let lines = lines();
+    c.bench_function("day05 floor", |b| {
+        b.iter(|| {
+            Floor::try_from((false, black_box(&lines[..])))
+                .unwrap()
+                .num_danger_areas()
+        })
+    });
+    c.bench_function("day05 dense floor", |b| {
+        b.iter(|| {
+            DenseFloor::try_from((false, black_box(&lines[..])))
+                .unwrap()
+                .num_danger_areas()
+        })
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/bin/day12.rs"]
+mod day12;
+
+use day12::Caves;
+
+/// Sample cave system
+const INPUT: [&str; 10] = [
+    "dc-end", "HN-start", "start-kj", "dc-start", "dc-HN", "LN-dc", "HN-end", "kj-sa", "kj-HN",
+    "kj-dc",
+];
+
+fn bench(c: &mut Criterion) {
+    let caves = Caves::try_from(&INPUT[..]).unwrap();
+    c.bench_function("day12 count paths with extra rule", |b| {
+        b.iter(|| black_box(&caves).count_paths(true))
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
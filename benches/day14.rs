@@ -0,0 +1,23 @@
+use advent_of_code_2021::Input;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/bin/day14.rs"]
+mod day14;
+
+/// Sample polymer template and insertion rules
+const INPUT: &str = "NNCB\n\nCH -> B\nHH -> N\nCB -> H\nNH -> C\nHB -> C\nHC -> B\nHN -> C\nNN -> C\nBH -> H\nNC -> B\nNB -> B\nBN -> B\nBB -> N\nBC -> B\nCC -> N\nCN -> C";
+
+fn bench(c: &mut Criterion) {
+    let (polymer, rules) = day14::parse_input(Input::from_str(INPUT)).unwrap();
+    c.bench_function("day14 process 40 steps", |b| {
+        b.iter_batched(
+            || polymer.clone(),
+            |mut polymer| polymer.process(black_box(40), &rules),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,22 @@
+use advent_of_code_2021::Input;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/bin/day15.rs"]
+mod day15;
+
+use day15::Map;
+
+/// Sample risk level map
+const INPUT: &str = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
+
+fn bench(c: &mut Criterion) {
+    let mut map = Map::from(Input::from_str(INPUT).digit_grid().unwrap());
+    map.enlarge(5);
+    c.bench_function("day15 pathfinder on enlarged map", |b| {
+        b.iter(|| black_box(&map).pathfinder())
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
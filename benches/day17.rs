@@ -0,0 +1,20 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+#[allow(dead_code)]
+#[path = "../src/bin/day17.rs"]
+mod day17;
+
+use day17::TargetArea;
+
+/// Sample target area
+const INPUT: &str = "target area: x=20..30, y=-10..-5";
+
+fn bench(c: &mut Criterion) {
+    let TargetArea(target_area) = INPUT.parse().unwrap();
+    c.bench_function("day17 brute force hits", |b| {
+        b.iter(|| day17::brute_force_hits(black_box(&target_area)))
+    });
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
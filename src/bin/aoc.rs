@@ -0,0 +1,109 @@
+use advent_of_code_2021::{timed, Answer, Input, Solution};
+use std::{env, error};
+use thiserror::Error;
+
+#[allow(dead_code)]
+#[path = "day01.rs"]
+mod day01;
+#[allow(dead_code)]
+#[path = "day02.rs"]
+mod day02;
+#[allow(dead_code)]
+#[path = "day06.rs"]
+mod day06;
+#[allow(dead_code)]
+#[path = "day12.rs"]
+mod day12;
+#[allow(dead_code)]
+#[path = "day14.rs"]
+mod day14;
+#[allow(dead_code)]
+#[path = "day15.rs"]
+mod day15;
+#[allow(dead_code)]
+#[path = "day17.rs"]
+mod day17;
+
+/// Unknown day error
+#[derive(Debug, Error)]
+#[error("No solution for day {0}")]
+struct UnknownDay(usize);
+
+/// Both parts of a day's solution
+type Parts = [fn(Input) -> Answer; 2];
+
+/// Day and parts of a solution
+fn solution<S: Solution>() -> (usize, Parts) {
+    (S::DAY, [S::part1, S::part2])
+}
+
+/// Find solution parts for the given day
+fn parts(day: usize) -> Result<Parts, UnknownDay> {
+    [
+        solution::<day01::Day01>(),
+        solution::<day02::Day02>(),
+        solution::<day06::Day06>(),
+        solution::<day12::Day12>(),
+        solution::<day14::Day14>(),
+        solution::<day15::Day15>(),
+        solution::<day17::Day17>(),
+    ]
+    .into_iter()
+    .find(|(d, _parts)| *d == day)
+    .map(|(_d, parts)| parts)
+    .ok_or(UnknownDay(day))
+}
+
+fn main() -> Result<(), Box<dyn error::Error>> {
+    let days: Vec<usize> = env::args()
+        .skip(1)
+        .map(|arg| arg.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| "Usage: aoc <day>...")?;
+    if days.is_empty() {
+        return Err("Usage: aoc <day>...".into());
+    }
+
+    for day in days {
+        for (i, part) in parts(day)?.into_iter().enumerate() {
+            let (answer, elapsed) = timed(day, part)?;
+            println!("Day {} part {}: {} ({:?})", day, i + 1, answer, elapsed);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(day: usize, input: &str) -> [String; 2] {
+        parts(day)
+            .unwrap()
+            .map(|part| part(Input::from_str(input)).unwrap())
+    }
+
+    #[test]
+    fn dispatch() {
+        let depths = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+        assert_eq!(solve(1, depths), ["7", "5"]);
+        let course = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2";
+        assert_eq!(solve(2, course), ["150", "900"]);
+        assert_eq!(solve(6, "3,4,3,1,2"), ["5934", "26984457539"]);
+        let caves = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
+        assert_eq!(solve(12, caves), ["10", "36"]);
+        let polymer = "NNCB\n\nCH -> B\nHH -> N\nCB -> H\nNH -> C\nHB -> C\nHC -> B\nHN -> C\nNN -> C\nBH -> H\nNC -> B\nNB -> B\nBN -> B\nBB -> N\nBC -> B\nCC -> N\nCN -> C";
+        assert_eq!(solve(14, polymer), ["1588", "2188189693529"]);
+        let map = "1163751742\n1381373672\n2136511328\n3694931569\n7463417111\n1319128137\n1359912421\n3125421639\n1293138521\n2311944581";
+        assert_eq!(solve(15, map), ["40", "315"]);
+        assert_eq!(solve(17, "target area: x=20..30, y=-10..-5"), ["45", "112"]);
+    }
+
+    #[test]
+    fn unknown_day() {
+        assert!(matches!(parts(3), Err(UnknownDay(3))));
+        assert!(matches!(parts(0), Err(UnknownDay(0))));
+        assert!(matches!(parts(26), Err(UnknownDay(26))));
+    }
+}
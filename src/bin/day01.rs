@@ -1,15 +1,65 @@
-use advent_of_code_2021::Input;
+use advent_of_code_2021::{Answer, Input, Solution};
 use itertools::Itertools;
+use std::collections::VecDeque;
 use std::error;
+use std::ops::{Add, Sub};
 
-fn count_increasing(iter: impl Iterator<Item = u32>) -> usize {
+fn count_changes<T: Copy + PartialOrd>(
+    iter: impl Iterator<Item = T>,
+    pred: impl Fn(&T, &T) -> bool,
+) -> usize {
     iter.tuple_windows::<(_, _)>()
-        .filter(|(a, b)| b > a)
+        .filter(|(a, b)| pred(a, b))
         .count()
 }
 
-fn sliding_window_sum(iter: impl Iterator<Item = u32>) -> impl Iterator<Item = u32> {
-    iter.tuple_windows::<(_, _, _)>().map(|(a, b, c)| a + b + c)
+fn count_increasing<T: Copy + PartialOrd>(iter: impl Iterator<Item = T>) -> usize {
+    count_changes(iter, |a, b| b > a)
+}
+
+fn count_decreasing<T: Copy + PartialOrd>(iter: impl Iterator<Item = T>) -> usize {
+    count_changes(iter, |a, b| b < a)
+}
+
+fn windowed_sums<T>(iter: impl Iterator<Item = T>, window: usize) -> impl Iterator<Item = T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    let mut values = VecDeque::with_capacity(window + 1);
+    let mut sum = T::default();
+    iter.filter_map(move |n| {
+        values.push_back(n);
+        sum = sum + n;
+        if values.len() > window {
+            sum = sum - values.pop_front()?;
+        }
+        (values.len() == window).then_some(sum)
+    })
+}
+
+fn sliding_window_sum<T>(iter: impl Iterator<Item = T>) -> impl Iterator<Item = T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    windowed_sums(iter, 3)
+}
+
+/// Day 1 solution
+#[allow(dead_code)]
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: usize = 1;
+
+    fn part1(input: Input) -> Answer {
+        let depths: Vec<u32> = input.parsed_lines().try_collect()?;
+        Ok(count_increasing(depths.into_iter()).to_string())
+    }
+
+    fn part2(input: Input) -> Answer {
+        let depths: Vec<u32> = input.parsed_lines().try_collect()?;
+        Ok(count_increasing(sliding_window_sum(depths.into_iter())).to_string())
+    }
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
@@ -18,6 +68,9 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let increasing_depths = count_increasing(depths.iter().copied());
     println!("Increasing depths: {}", increasing_depths);
 
+    let decreasing_depths = count_decreasing(depths.iter().copied());
+    println!("Decreasing depths: {}", decreasing_depths);
+
     let increasing_depths = count_increasing(sliding_window_sum(depths.iter().copied()));
     println!("Increasing sliding-window depths: {}", increasing_depths);
 
@@ -35,6 +88,12 @@ mod tests {
         assert_eq!(count_increasing(DEPTHS.iter().copied()), 7);
     }
 
+    #[test]
+    fn decreasing() {
+        assert_eq!(count_decreasing(DEPTHS.iter().copied()), 2);
+        assert_eq!(count_changes(DEPTHS.iter().copied(), |a, b| b >= a), 7);
+    }
+
     #[test]
     fn part_2() {
         assert_eq!(
@@ -42,4 +101,53 @@ mod tests {
             5
         );
     }
+
+    #[test]
+    fn windows() {
+        let sums: Vec<_> = windowed_sums(DEPTHS.iter().copied(), 1).collect();
+        assert_eq!(sums, DEPTHS);
+        let sums: Vec<_> = windowed_sums(DEPTHS.iter().copied(), 2).collect();
+        assert_eq!(sums, [399, 408, 418, 410, 407, 447, 509, 529, 523]);
+        let sums: Vec<_> = windowed_sums(DEPTHS.iter().copied(), 3).collect();
+        assert_eq!(sums, [607, 618, 618, 617, 647, 716, 769, 792]);
+        let sums: Vec<_> = windowed_sums(DEPTHS.iter().copied(), 4).collect();
+        assert_eq!(sums, [817, 818, 825, 857, 916, 976, 1032]);
+
+        assert_eq!(
+            count_increasing(windowed_sums(DEPTHS.iter().copied(), 1)),
+            7
+        );
+        assert_eq!(
+            count_increasing(windowed_sums(DEPTHS.iter().copied(), 2)),
+            5
+        );
+        assert_eq!(
+            count_increasing(windowed_sums(DEPTHS.iter().copied(), 3)),
+            5
+        );
+        assert_eq!(
+            count_increasing(windowed_sums(DEPTHS.iter().copied(), 4)),
+            6
+        );
+    }
+
+    #[test]
+    fn large_values() {
+        let depths: [u64; 4] = [2_000_000_000, 3_000_000_000, 4_000_000_000, 1_000_000_000];
+        let sums: Vec<_> = sliding_window_sum(depths.iter().copied()).collect();
+        assert_eq!(sums, [9_000_000_000, 8_000_000_000]);
+        assert_eq!(count_decreasing(sums.into_iter()), 1);
+
+        let depths: [i64; 4] = [-3_000_000_000, 3_000_000_000, 4_000_000_000, -1];
+        assert_eq!(
+            count_increasing(windowed_sums(depths.iter().copied(), 2)),
+            1
+        );
+    }
+
+    #[test]
+    fn short_window() {
+        assert_eq!(windowed_sums(DEPTHS.iter().copied(), 11).count(), 0);
+        assert_eq!(windowed_sums([1, 2].into_iter(), 3).count(), 0);
+    }
 }
@@ -1,4 +1,4 @@
-use advent_of_code_2021::Input;
+use advent_of_code_2021::{Answer, Input, Solution};
 use itertools::Itertools;
 use std::error;
 use std::str::FromStr;
@@ -15,6 +15,7 @@ enum Movement {
     Forward(u32),
     Down(u32),
     Up(u32),
+    Strafe(i32),
 }
 
 impl FromStr for Movement {
@@ -22,6 +23,10 @@ impl FromStr for Movement {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (direction, distance) = s.split_once(' ').ok_or(BadMovement)?;
+        if direction == "strafe" {
+            let distance: i32 = distance.parse().map_err(|_| BadMovement)?;
+            return Ok(Movement::Strafe(distance));
+        }
         let distance: u32 = distance.parse().map_err(|_| BadMovement)?;
         match direction {
             "forward" => Ok(Movement::Forward(distance)),
@@ -32,24 +37,75 @@ impl FromStr for Movement {
     }
 }
 
+/// Submarine navigation mode
+trait Navigator {
+    /// Apply a single movement
+    fn apply(&mut self, movement: &Movement);
+
+    /// Revert a single movement
+    #[allow(dead_code)]
+    fn revert(&mut self, movement: &Movement);
+
+    /// Product of position and depth
+    fn result(&self) -> u32;
+
+    /// Move along the given course
+    fn go(&mut self, course: &[Movement]) {
+        for movement in course {
+            self.apply(movement);
+        }
+    }
+
+    /// Retrace the given course backwards
+    #[allow(dead_code)]
+    fn undo(&mut self, course: &[Movement]) {
+        for movement in course.iter().rev() {
+            self.revert(movement);
+        }
+    }
+}
+
+impl Movement {
+    /// Movement that reverses this movement (forward stays forward, but is
+    /// meant to be applied backwards)
+    fn inverse(&self) -> Movement {
+        match *self {
+            Movement::Forward(distance) => Movement::Forward(distance),
+            Movement::Down(distance) => Movement::Up(distance),
+            Movement::Up(distance) => Movement::Down(distance),
+            Movement::Strafe(distance) => Movement::Strafe(-distance),
+        }
+    }
+}
+
 /// Submarine position
 #[derive(Debug, Default, Clone)]
 struct Position {
     position: u32,
     depth: u32,
+    lateral: i32,
 }
 
-impl Position {
-    /// Move along the given course
-    fn go(&mut self, course: &[Movement]) {
-        for movement in course {
-            match movement {
-                Movement::Forward(distance) => self.position += distance,
-                Movement::Down(distance) => self.depth += distance,
-                Movement::Up(distance) => self.depth -= distance,
-            }
+impl Navigator for Position {
+    fn apply(&mut self, movement: &Movement) {
+        match movement {
+            Movement::Forward(distance) => self.position += distance,
+            Movement::Down(distance) => self.depth += distance,
+            Movement::Up(distance) => self.depth -= distance,
+            Movement::Strafe(distance) => self.lateral += distance,
         }
     }
+
+    fn revert(&mut self, movement: &Movement) {
+        match movement.inverse() {
+            Movement::Forward(distance) => self.position -= distance,
+            inverse => self.apply(&inverse),
+        }
+    }
+
+    fn result(&self) -> u32 {
+        self.position * self.depth
+    }
 }
 
 /// Submarine position (part 2)
@@ -58,22 +114,59 @@ struct ExactPosition {
     position: u32,
     depth: u32,
     aim: u32,
+    lateral: i32,
 }
 
-impl ExactPosition {
-    /// Move along the given course
-    fn go(&mut self, course: &[Movement]) {
-        for movement in course {
-            match movement {
-                Movement::Forward(distance) => {
-                    self.position += distance;
-                    self.depth += self.aim * distance;
-                }
-                Movement::Down(distance) => self.aim += distance,
-                Movement::Up(distance) => self.aim -= distance,
+impl Navigator for ExactPosition {
+    fn apply(&mut self, movement: &Movement) {
+        match movement {
+            Movement::Forward(distance) => {
+                self.position += distance;
+                self.depth += self.aim * distance;
+            }
+            Movement::Down(distance) => self.aim += distance,
+            Movement::Up(distance) => self.aim -= distance,
+            Movement::Strafe(distance) => self.lateral += distance,
+        }
+    }
+
+    fn revert(&mut self, movement: &Movement) {
+        // Walking the course backwards, the current aim is the aim that was
+        // in effect when the movement was originally applied
+        match movement.inverse() {
+            Movement::Forward(distance) => {
+                self.position -= distance;
+                self.depth -= self.aim * distance;
             }
+            inverse => self.apply(&inverse),
         }
     }
+
+    fn result(&self) -> u32 {
+        self.position * self.depth
+    }
+}
+
+/// Day 2 solution
+#[allow(dead_code)]
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: usize = 2;
+
+    fn part1(input: Input) -> Answer {
+        let course: Vec<Movement> = input.parsed_lines().try_collect()?;
+        let mut position = Position::default();
+        position.go(&course);
+        Ok(position.result().to_string())
+    }
+
+    fn part2(input: Input) -> Answer {
+        let course: Vec<Movement> = input.parsed_lines().try_collect()?;
+        let mut position = ExactPosition::default();
+        position.go(&course);
+        Ok(position.result().to_string())
+    }
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
@@ -82,19 +175,21 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let mut position = Position::default();
     position.go(&course);
     println!(
-        "Final position: {}, depth: {}, product: {}",
+        "Final position: {}, depth: {}, lateral: {}, product: {}",
         position.position,
         position.depth,
-        position.position * position.depth,
+        position.lateral,
+        position.result(),
     );
 
     let mut position = ExactPosition::default();
     position.go(&course);
     println!(
-        "Final exact position: {}, depth: {}, product: {}",
+        "Final exact position: {}, depth: {}, lateral: {}, product: {}",
         position.position,
         position.depth,
-        position.position * position.depth,
+        position.lateral,
+        position.result(),
     );
 
     Ok(())
@@ -117,6 +212,20 @@ mod tests {
         COURSE.iter().map(|s| s.parse().unwrap()).collect()
     }
 
+    fn strafe_course() -> Vec<Movement> {
+        [
+            "forward 5",
+            "strafe 4",
+            "down 5",
+            "strafe -7",
+            "forward 8",
+            "up 3",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect()
+    }
+
     #[test]
     fn parse() {
         assert_eq!(
@@ -132,6 +241,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_strafe() {
+        assert_eq!("strafe 4".parse::<Movement>().unwrap(), Movement::Strafe(4));
+        assert_eq!(
+            "strafe -2".parse::<Movement>().unwrap(),
+            Movement::Strafe(-2)
+        );
+        assert!("strafe x".parse::<Movement>().is_err());
+        assert!("sideways 4".parse::<Movement>().is_err());
+        assert!("up -4".parse::<Movement>().is_err());
+    }
+
     #[test]
     fn part_1() {
         let mut position = Position::default();
@@ -147,4 +268,66 @@ mod tests {
         assert_eq!(position.position, 15);
         assert_eq!(position.depth, 60);
     }
+
+    #[test]
+    fn navigators() {
+        let mut navigators: [Box<dyn Navigator>; 2] = [
+            Box::new(Position::default()),
+            Box::new(ExactPosition::default()),
+        ];
+        for navigator in &mut navigators {
+            navigator.go(&course());
+        }
+        assert_eq!(navigators[0].result(), 150);
+        assert_eq!(navigators[1].result(), 900);
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(Movement::Forward(5).inverse(), Movement::Forward(5));
+        assert_eq!(Movement::Down(5).inverse(), Movement::Up(5));
+        assert_eq!(Movement::Up(3).inverse(), Movement::Down(3));
+    }
+
+    #[test]
+    fn undo() {
+        let mut position = Position::default();
+        position.go(&course());
+        position.undo(&course());
+        assert_eq!((position.position, position.depth), (0, 0));
+
+        let mut position = Position::default();
+        position.go(&strafe_course());
+        position.undo(&strafe_course());
+        assert_eq!(position.lateral, 0);
+
+        let mut position = ExactPosition::default();
+        position.go(&course());
+        position.undo(&course()[3..]);
+        assert_eq!(
+            (position.position, position.depth, position.aim),
+            (13, 40, 5)
+        );
+        position.undo(&course()[..3]);
+        assert_eq!((position.position, position.depth, position.aim), (0, 0, 0));
+    }
+
+    #[test]
+    fn strafe() {
+        let mut position = Position::default();
+        position.go(&strafe_course());
+        assert_eq!(
+            (position.position, position.depth, position.lateral),
+            (13, 2, -3)
+        );
+        assert_eq!(position.result(), 26);
+
+        let mut position = ExactPosition::default();
+        position.go(&strafe_course());
+        assert_eq!(
+            (position.position, position.depth, position.lateral),
+            (13, 40, -3)
+        );
+        assert_eq!(position.result(), 520);
+    }
 }
@@ -1,6 +1,7 @@
 use advent_of_code_2021::Input;
 use itertools::Itertools;
 use std::error;
+use std::fmt;
 use std::num::ParseIntError;
 
 /// Distribution of bits
@@ -13,7 +14,7 @@ enum Distribution {
 
 /// Diagnostic report
 #[derive(Debug, Clone)]
-struct Diag(Vec<u16>, usize);
+struct Diag(Vec<u32>, usize);
 
 impl Diag {
     /// Create new dignostic report
@@ -21,7 +22,7 @@ impl Diag {
         Ok(Self(
             lines
                 .iter()
-                .map(|line| u16::from_str_radix(line.as_ref(), 2))
+                .map(|line| u32::from_str_radix(line.as_ref(), 2))
                 .try_collect()?,
             lines
                 .iter()
@@ -74,34 +75,55 @@ impl Diag {
         self.0.retain(|n| (*n & (1 << i) > 0) == bit);
     }
 
-    /// Oxygen generator rating
-    fn oxygen(&self) -> u16 {
+    /// Rating found by repeatedly keeping entries with the most (or least)
+    /// common bit, keeping entries with the tiebreak bit if equally common
+    fn rating(&self, keep_most_common: bool, tiebreak: bool) -> u32 {
         let mut diag = self.clone();
         for i in (0..self.1).rev() {
             if diag.0.len() < 2 {
                 break;
             }
-            diag.filter(i, diag.distribution(i) != Distribution::MostCommonZero);
+            let bit = match diag.distribution(i) {
+                Distribution::EquallyCommon => tiebreak,
+                Distribution::MostCommonOne => keep_most_common,
+                Distribution::MostCommonZero => !keep_most_common,
+            };
+            diag.filter(i, bit);
         }
         diag.0[0]
     }
 
+    /// Oxygen generator rating
+    fn oxygen(&self) -> u32 {
+        self.rating(true, true)
+    }
+
     /// CO2 scrubber rating
-    fn co2(&self) -> u16 {
-        let mut diag = self.clone();
-        for i in (0..self.1).rev() {
-            if diag.0.len() < 2 {
-                break;
-            }
-            diag.filter(i, diag.distribution(i) == Distribution::MostCommonZero);
-        }
-        diag.0[0]
+    fn co2(&self) -> u32 {
+        self.rating(false, false)
     }
 
     /// Life support rating
     fn life_support(&self) -> usize {
         self.oxygen() as usize * self.co2() as usize
     }
+
+    /// Entries as binary strings padded to the report's width
+    fn entries_binary(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|n| format!("{:0width$b}", n, width = self.1))
+            .collect()
+    }
+}
+
+impl fmt::Display for Diag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in self.entries_binary() {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
@@ -134,6 +156,18 @@ mod tests {
         "00010", "01010",
     ];
 
+    #[test]
+    fn display() {
+        let diag = Diag::new(&DIAG).unwrap();
+        assert_eq!(diag.entries_binary(), DIAG);
+        assert!(diag.to_string().starts_with("00100\n11110\n10110\n10111\n"));
+
+        let mut diag = Diag::new(&DIAG).unwrap();
+        diag.filter(4, false);
+        diag.filter(3, true);
+        assert_eq!(diag.to_string(), "01111\n01010\n");
+    }
+
     #[test]
     fn part_1() {
         let diag = Diag::new(&DIAG).unwrap();
@@ -147,4 +181,24 @@ mod tests {
         assert_eq!(diag.oxygen(), 23);
         assert_eq!(diag.co2(), 10);
     }
+
+    #[test]
+    fn rating() {
+        let diag = Diag::new(&DIAG).unwrap();
+        assert_eq!(diag.rating(true, true), 23);
+        assert_eq!(diag.rating(false, false), 10);
+        assert_eq!(diag.rating(true, false), 22);
+        assert_eq!(diag.rating(false, true), 15);
+    }
+
+    #[test]
+    fn wide() {
+        let lines: Vec<_> = DIAG.iter().map(|line| line.repeat(4)).collect();
+        let diag = Diag::new(&lines).unwrap();
+        assert_eq!(diag.1, 20);
+        assert_eq!(diag.gamma(), 22 * 0b00001_00001_00001_00001);
+        assert_eq!(diag.epsilon(), 9 * 0b00001_00001_00001_00001);
+        assert_eq!(diag.oxygen(), 23 * 0b00001_00001_00001_00001);
+        assert_eq!(diag.co2(), 10 * 0b00001_00001_00001_00001);
+    }
 }
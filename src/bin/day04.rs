@@ -2,6 +2,7 @@ use advent_of_code_2021::Input;
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::error;
+use std::fmt;
 use thiserror::Error;
 
 /// Input parse error
@@ -12,16 +13,14 @@ struct ParseError;
 /// Bingo board
 #[derive(Debug, Clone)]
 struct Board {
-    numbers: [[u8; 5]; 5],
-    marks: [[bool; 5]; 5],
+    numbers: Vec<Vec<u8>>,
+    marks: Vec<Vec<bool>>,
+    diagonals: bool,
 }
 
-impl From<[[u8; 5]; 5]> for Board {
-    fn from(numbers: [[u8; 5]; 5]) -> Self {
-        Self {
-            numbers,
-            marks: [[false; 5]; 5],
-        }
+impl<const N: usize> From<[[u8; N]; N]> for Board {
+    fn from(numbers: [[u8; N]; N]) -> Self {
+        Self::new(numbers.iter().map(|row| row.to_vec()).collect())
     }
 }
 
@@ -29,45 +28,68 @@ impl<S: AsRef<str>> TryFrom<&[S]> for Board {
     type Error = ParseError;
 
     fn try_from(lines: &[S]) -> Result<Self, Self::Error> {
-        fn parse_line(line: &str) -> Result<[u8; 5], ParseError> {
-            let values: Vec<u8> = line
-                .split_whitespace()
-                .map(|s| s.parse())
-                .try_collect()
-                .map_err(|_| ParseError)?;
-            values.try_into().map_err(|_| ParseError)
-        }
-
-        let rows: Vec<[u8; 5]> = lines
+        let numbers: Vec<Vec<u8>> = lines
             .iter()
-            .map(|line| parse_line(line.as_ref()))
+            .map(|line| {
+                line.as_ref()
+                    .split_whitespace()
+                    .map(|s| s.parse())
+                    .try_collect()
+                    .map_err(|_| ParseError)
+            })
             .try_collect()?;
-        let numbers: [[u8; 5]; 5] = rows.try_into().map_err(|_| ParseError)?;
+        if numbers.is_empty() || numbers.iter().any(|row| row.len() != numbers.len()) {
+            return Err(ParseError);
+        }
 
-        Ok(Self::from(numbers))
+        Ok(Self::new(numbers))
     }
 }
 
 impl Board {
+    /// Create new board with the given (square) grid of numbers
+    fn new(numbers: Vec<Vec<u8>>) -> Self {
+        let marks = numbers.iter().map(|row| vec![false; row.len()]).collect();
+        Self {
+            numbers,
+            marks,
+            diagonals: false,
+        }
+    }
+
+    /// Also count both main diagonals as winning lines
+    #[allow(dead_code)]
+    fn with_diagonals(mut self, diagonals: bool) -> Self {
+        self.diagonals = diagonals;
+        self
+    }
+
     /// Height of board
-    const fn height(&self) -> usize {
+    fn height(&self) -> usize {
         self.numbers.len()
     }
 
     /// Width of board
-    const fn width(&self) -> usize {
+    fn width(&self) -> usize {
         self.numbers[0].len()
     }
 
+    /// Whether any line through the given cell is fully marked
+    fn is_winning_line(&self, x: usize, y: usize) -> bool {
+        let n = self.width();
+        (0..self.height()).all(|y| self.marks[y][x])
+            || (0..n).all(|x| self.marks[y][x])
+            || self.diagonals && x == y && (0..n).all(|i| self.marks[i][i])
+            || self.diagonals && x + y + 1 == n && (0..n).all(|i| self.marks[i][n - 1 - i])
+    }
+
     /// Mark given number on board, return score if won
     fn mark(&mut self, number: u8) -> Option<u32> {
         for y in 0..self.height() {
             for x in 0..self.width() {
                 if self.numbers[y][x] == number {
                     self.marks[y][x] = true;
-                    if (0..self.height()).all(|y| self.marks[y][x])
-                        || (0..self.width()).all(|x| self.marks[y][x])
-                    {
+                    if self.is_winning_line(x, y) {
                         return Some(self.score() * number as u32);
                     }
                 }
@@ -76,6 +98,25 @@ impl Board {
         None
     }
 
+    /// Clear mark of given number (all occurrences)
+    #[allow(dead_code)]
+    fn unmark(&mut self, number: u8) {
+        for (numbers, marks) in self.numbers.iter().zip(&mut self.marks) {
+            for (n, mark) in numbers.iter().zip(marks) {
+                if *n == number {
+                    *mark = false;
+                }
+            }
+        }
+    }
+
+    /// Clear all marks
+    fn reset(&mut self) {
+        for marks in &mut self.marks {
+            marks.fill(false);
+        }
+    }
+
     /// Calculate score (regardless of winning condition)
     fn score(&self) -> u32 {
         (0..self.height())
@@ -89,6 +130,29 @@ impl Board {
     }
 }
 
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .numbers
+            .iter()
+            .flatten()
+            .max()
+            .map_or(1, |n| n.to_string().len());
+        for (numbers, marks) in self.numbers.iter().zip(&self.marks) {
+            let cells = numbers.iter().zip(marks).map(|(n, mark)| {
+                let cell = if *mark {
+                    format!("({})", n)
+                } else {
+                    format!(" {} ", n)
+                };
+                format!("{:>width$}", cell, width = width + 2)
+            });
+            writeln!(f, "{}", cells.format(" "))?;
+        }
+        Ok(())
+    }
+}
+
 /// Bingo game
 struct Game<'a> {
     boards: &'a mut [Board],
@@ -100,6 +164,14 @@ impl<'a> Game<'a> {
         Self { boards }
     }
 
+    /// Clear marks on all boards to replay the game
+    #[allow(dead_code)]
+    fn reset(&mut self) {
+        for board in self.boards.iter_mut() {
+            board.reset();
+        }
+    }
+
     /// Play round with given number, return board and score of winners
     fn round(&mut self, number: u8) -> Vec<(usize, u32)> {
         self.boards
@@ -119,19 +191,24 @@ impl<'a> Game<'a> {
         None
     }
 
-    /// Play game with given sequence of numbers, return round, board and score of last winner
-    fn play_last(&mut self, numbers: &[u8]) -> Option<(usize, usize, u32)> {
+    /// Play game with given sequence of numbers, return round, board and score of all winners
+    /// in the order they won
+    fn winning_order(&mut self, numbers: &[u8]) -> Vec<(usize, usize, u32)> {
         let mut winners = HashSet::new();
-        let mut last_winner = None;
+        let mut order = Vec::new();
         for (r, number) in numbers.iter().enumerate() {
             for (b, score) in self.round(*number) {
-                if !winners.contains(&b) {
-                    last_winner = Some((r, b, score));
-                    winners.insert(b);
+                if winners.insert(b) {
+                    order.push((r, b, score));
                 }
             }
         }
-        last_winner
+        order
+    }
+
+    /// Play game with given sequence of numbers, return round, board and score of last winner
+    fn play_last(&mut self, numbers: &[u8]) -> Option<(usize, usize, u32)> {
+        self.winning_order(numbers).pop()
     }
 }
 
@@ -139,11 +216,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let mut input = Input::day(4)?;
     let numbers: Vec<u8> = input.line()?.split(',').map(|s| s.parse()).try_collect()?;
 
-    let blocks: Vec<_> = input.blocks().try_collect()?;
-    let mut boards: Vec<_> = blocks
-        .into_iter()
-        .map(|lines| Board::try_from(&lines[..]))
-        .try_collect()?;
+    let mut boards: Vec<Board> = input.blocks_parsed().try_collect()?;
 
     let mut boards1 = boards.clone();
     let mut game = Game::new(&mut boards1);
@@ -212,4 +285,101 @@ mod tests {
         let mut game = Game::new(&mut boards);
         assert_eq!(game.play_last(&NUMBERS), Some((14, 1, 1924)));
     }
+
+    #[test]
+    fn winning_order() {
+        let mut boards1 = boards();
+        let mut game = Game::new(&mut boards1);
+        let order = game.winning_order(&NUMBERS);
+        assert_eq!(order.len(), 3);
+        assert_eq!(order[0], (11, 2, 4512));
+        assert_eq!(order[1].1, 0);
+        assert_eq!(order[2], (14, 1, 1924));
+
+        let mut boards2 = boards();
+        let mut game = Game::new(&mut boards2);
+        let order = game.winning_order(&NUMBERS[..12]);
+        assert_eq!(order, [(11, 2, 4512)]);
+    }
+
+    #[test]
+    fn reset() {
+        let mut board = Board::from([[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        board.mark(1);
+        board.mark(2);
+        assert_eq!(board.score(), 42);
+        board.reset();
+        assert_eq!(board.score(), 45);
+        assert_eq!(board.mark(1), None);
+        assert_eq!(board.mark(4), None);
+        assert_eq!(board.mark(7), Some((2 + 3 + 5 + 6 + 8 + 9) * 7));
+
+        let mut boards = boards();
+        let mut game = Game::new(&mut boards);
+        assert_eq!(game.play(&NUMBERS), Some((11, 2, 4512)));
+        game.reset();
+        assert_eq!(game.play_last(&NUMBERS), Some((14, 1, 1924)));
+    }
+
+    #[test]
+    fn unmark() {
+        let mut board = Board::from([[1, 2, 1], [4, 5, 6], [7, 1, 9]]);
+        board.mark(1);
+        board.mark(5);
+        assert_eq!(board.score(), 2 + 4 + 6 + 7 + 9);
+        board.unmark(1);
+        assert_eq!(board.score(), 1 + 2 + 1 + 4 + 6 + 7 + 1 + 9);
+        board.unmark(3);
+        assert_eq!(board.mark(2), None);
+        assert_eq!(board.mark(8), None);
+    }
+
+    #[test]
+    fn display() {
+        let mut board = Board::from([[1, 2, 3], [4, 15, 6], [7, 8, 19]]);
+        board.mark(1);
+        board.mark(15);
+        assert_eq!(
+            board.to_string(),
+            " (1)   2    3 \n  4  (15)   6 \n  7    8   19 \n"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let board = Board::try_from(&["1 2 3", " 4  5 6", "7 8 9"][..]).unwrap();
+        assert_eq!(board.numbers, [[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        assert_eq!((board.width(), board.height()), (3, 3));
+
+        assert!(Board::try_from(&["1 2 3", "4 5 6"][..]).is_err());
+        assert!(Board::try_from(&["1 2", "4 5 6", "7 8 9"][..]).is_err());
+        assert!(Board::try_from(&[] as &[&str]).is_err());
+    }
+
+    #[test]
+    fn small_board() {
+        let mut board = Board::from([[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        assert_eq!(board.mark(2), None);
+        assert_eq!(board.mark(4), None);
+        assert_eq!(board.mark(8), None);
+        assert_eq!(board.mark(5), Some((1 + 3 + 6 + 7 + 9) * 5));
+    }
+
+    #[test]
+    fn diagonals() {
+        let mut board = Board::from([[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        assert_eq!(board.mark(1), None);
+        assert_eq!(board.mark(5), None);
+        assert_eq!(board.mark(9), None);
+
+        let mut board = Board::from([[1, 2, 3], [4, 5, 6], [7, 8, 9]]).with_diagonals(true);
+        assert_eq!(board.mark(1), None);
+        assert_eq!(board.mark(5), None);
+        assert_eq!(board.mark(9), Some((2 + 3 + 4 + 6 + 7 + 8) * 9));
+
+        let mut board = Board::from([[1, 2, 3], [4, 5, 6], [7, 8, 9]]).with_diagonals(true);
+        assert_eq!(board.mark(3), None);
+        assert_eq!(board.mark(7), None);
+        assert_eq!(board.mark(5), Some((1 + 2 + 4 + 6 + 8 + 9) * 5));
+    }
 }
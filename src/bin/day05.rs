@@ -1,86 +1,26 @@
-use advent_of_code_2021::Input;
+use advent_of_code_2021::{Input, Point};
 use itertools::Itertools;
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::error;
-use std::ops::{Add, Mul};
+use std::fmt;
 use std::str::FromStr;
 use thiserror::Error;
 
 /// Input parse error
 #[derive(Debug, Error)]
 #[error("Input parse error")]
-struct ParseError;
+pub(crate) struct ParseError;
 
-/// Coordinate
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Coordinate {
-    x: usize,
-    y: usize,
-}
-
-impl FromStr for Coordinate {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (x, y) = s.split_once(',').ok_or(ParseError).and_then(|(sx, sy)| {
-            Ok((
-                sx.trim().parse().map_err(|_| ParseError)?,
-                sy.trim().parse().map_err(|_| ParseError)?,
-            ))
-        })?;
-        Ok(Self::new(x, y))
-    }
-}
-
-impl Add<Offset> for Coordinate {
-    type Output = Self;
-
-    fn add(self, offset: Offset) -> Self {
-        Self {
-            x: (self.x as isize + offset.x) as usize,
-            y: (self.y as isize + offset.y) as usize,
-        }
-    }
-}
-
-impl Coordinate {
-    /// Create a new coordinate with given x and y position
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-}
-
-/// Coordinate offset
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Offset {
-    x: isize,
-    y: isize,
-}
-
-impl Mul<isize> for Offset {
-    type Output = Self;
-
-    fn mul(self, factor: isize) -> Self {
-        Self {
-            x: self.x * factor,
-            y: self.y * factor,
-        }
-    }
-}
-
-impl Offset {
-    /// Create a new offset with given x and y values
-    fn new(x: isize, y: isize) -> Self {
-        Self { x, y }
-    }
-}
+/// Unsupported line error
+#[derive(Debug, Error)]
+#[error("Unsupported line from {},{} to {},{}", .0.from.x, .0.from.y, .0.to.x, .0.to.y)]
+pub(crate) struct UnsupportedLine(Line);
 
 /// Line of vents
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Line {
-    from: Coordinate,
-    to: Coordinate,
+pub(crate) struct Line {
+    from: Point,
+    to: Point,
 }
 
 impl FromStr for Line {
@@ -102,23 +42,13 @@ impl FromStr for Line {
 
 impl Line {
     /// Create a new line with given coordinates
-    fn new(from: Coordinate, to: Coordinate) -> Self {
+    fn new(from: Point, to: Point) -> Self {
         Self { from, to }
     }
 
     /// Direction of line
-    fn direction(&self) -> Offset {
-        let ofsx = match self.from.x.cmp(&self.to.x) {
-            Ordering::Less => 1,
-            Ordering::Equal => 0,
-            Ordering::Greater => -1,
-        };
-        let ofsy = match self.from.y.cmp(&self.to.y) {
-            Ordering::Less => 1,
-            Ordering::Equal => 0,
-            Ordering::Greater => -1,
-        };
-        Offset::new(ofsx, ofsy)
+    fn direction(&self) -> Point {
+        self.from.direction_to(self.to)
     }
 
     /// Is diagonal line
@@ -127,48 +57,53 @@ impl Line {
         direction.x != 0 && direction.y != 0
     }
 
-    /// Return a list of coordinates the line goes through
-    fn coordinates(&self) -> Vec<Coordinate> {
-        let minx = usize::min(self.from.x, self.to.x);
-        let maxx = usize::max(self.from.x, self.to.x);
-        let lenx = maxx - minx;
-        let miny = usize::min(self.from.y, self.to.y);
-        let maxy = usize::max(self.from.y, self.to.y);
-        let leny = maxy - miny;
-        if self.from.x == self.to.x {
-            (miny..=maxy)
-                .map(|y| Coordinate::new(self.from.x, y))
-                .collect()
-        } else if self.from.y == self.to.y {
-            (minx..=maxx)
-                .map(|x| Coordinate::new(x, self.from.y))
-                .collect()
-        } else if lenx == leny {
-            let direction = self.direction();
-            (0..=lenx as isize)
-                .map(|i| self.from + direction * i)
-                .collect()
-        } else {
-            panic!("Only horizontal, vertical and diagonal lines are supported");
+    /// Return a list of coordinates the line goes through (only lines with an
+    /// integer slope or inverse slope are supported)
+    fn coordinates(&self) -> Result<Vec<Point>, UnsupportedLine> {
+        let lenx = self.from.x.abs_diff(self.to.x);
+        let leny = self.from.y.abs_diff(self.to.y);
+        let steps = gcd(lenx, leny);
+        if steps == 0 {
+            return Ok(vec![self.from]);
         }
+        if steps != usize::min(lenx, leny) && usize::min(lenx, leny) != 0 {
+            return Err(UnsupportedLine(*self));
+        }
+        let direction = self.direction();
+        let step = Point::new(
+            direction.x * (lenx / steps) as isize,
+            direction.y * (leny / steps) as isize,
+        );
+        Ok((0..=steps as isize).map(|i| self.from + step * i).collect())
+    }
+}
+
+/// Greatest common divisor
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
 }
 
 /// Ocean floow
 #[derive(Debug)]
-struct Floor {
-    density: HashMap<Coordinate, usize>,
+pub(crate) struct Floor {
+    density: HashMap<Point, usize>,
     ignore_diagonals: bool,
 }
 
-impl From<(bool, &[Line])> for Floor {
-    fn from(input: (bool, &[Line])) -> Self {
+impl TryFrom<(bool, &[Line])> for Floor {
+    type Error = UnsupportedLine;
+
+    fn try_from(input: (bool, &[Line])) -> Result<Self, Self::Error> {
         let (ignore_diagonals, lines) = input;
         let mut floor = Self::new(ignore_diagonals);
         for line in lines {
-            floor.add_line(line);
+            floor.add_line(line)?;
         }
-        floor
+        Ok(floor)
     }
 }
 
@@ -182,35 +117,150 @@ impl Floor {
     }
 
     /// Add a line of vents to the ocean floor
-    fn add_line(&mut self, line: &Line) {
+    fn add_line(&mut self, line: &Line) -> Result<(), UnsupportedLine> {
         if !(self.ignore_diagonals && line.is_diagonal()) {
-            for coord in line.coordinates() {
+            for coord in line.coordinates()? {
                 self.density
                     .entry(coord)
                     .and_modify(|e| *e += 1)
                     .or_insert(1);
             }
         }
+        Ok(())
+    }
+
+    /// Top-left and bottom-right coordinate of all vents
+    fn bounding_box(&self) -> (Point, Point) {
+        let (minx, maxx) = self
+            .density
+            .keys()
+            .map(|c| c.x)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let (miny, maxy) = self
+            .density
+            .keys()
+            .map(|c| c.y)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        (Point::new(minx, miny), Point::new(maxx, maxy))
     }
 
     /// Find number of danger areas (where density is >= 2)
-    fn num_danger_areas(&self) -> usize {
+    pub(crate) fn num_danger_areas(&self) -> usize {
         self.density.values().filter(|d| **d >= 2).count()
     }
+
+    /// Find number of areas with exactly the given density
+    fn count_with_density(&self, n: usize) -> usize {
+        self.density.values().filter(|d| **d == n).count()
+    }
+
+    /// Highest density of vents
+    fn max_density(&self) -> usize {
+        self.density.values().copied().max().unwrap_or(0)
+    }
+}
+
+/// Ocean floor backed by a flat grid, which is considerably faster than a hash
+/// map for large inputs. The grid covers the bounding box of all lines.
+#[derive(Debug)]
+pub(crate) struct DenseFloor {
+    density: Vec<usize>,
+    origin: Point,
+    width: usize,
+    ignore_diagonals: bool,
+}
+
+impl TryFrom<(bool, &[Line])> for DenseFloor {
+    type Error = UnsupportedLine;
+
+    fn try_from(input: (bool, &[Line])) -> Result<Self, Self::Error> {
+        let (ignore_diagonals, lines) = input;
+        let coords = || lines.iter().flat_map(|line| [line.from, line.to]);
+        let (minx, maxx) = coords()
+            .map(|c| c.x)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let (miny, maxy) = coords()
+            .map(|c| c.y)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let width = minx.abs_diff(maxx) + 1;
+        let height = miny.abs_diff(maxy) + 1;
+        let mut floor = Self {
+            density: vec![0; width * height],
+            origin: Point::new(minx, miny),
+            width,
+            ignore_diagonals,
+        };
+        for line in lines {
+            floor.add_line(line)?;
+        }
+        Ok(floor)
+    }
+}
+
+impl DenseFloor {
+    /// Add a line of vents to the ocean floor (must be within the floor's
+    /// bounds)
+    fn add_line(&mut self, line: &Line) -> Result<(), UnsupportedLine> {
+        if !(self.ignore_diagonals && line.is_diagonal()) {
+            for coord in line.coordinates()? {
+                let y = self.origin.y.abs_diff(coord.y);
+                let x = self.origin.x.abs_diff(coord.x);
+                self.density[y * self.width + x] += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find number of danger areas (where density is >= 2)
+    pub(crate) fn num_danger_areas(&self) -> usize {
+        self.density.iter().filter(|d| **d >= 2).count()
+    }
+}
+
+impl fmt::Display for Floor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min, max) = self.bounding_box();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                match self.density.get(&Point::new(x, y)) {
+                    None => write!(f, ".")?,
+                    Some(d) => write!(f, "{}", char::from_digit(*d as u32, 10).unwrap_or('#'))?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
     let lines: Vec<Line> = Input::day(5)?.parsed_lines().try_collect()?;
 
-    let floor = Floor::from((true, &lines[..]));
+    let floor = Floor::try_from((true, &lines[..]))?;
     println!("Number of danger areas: {}", floor.num_danger_areas());
 
-    let floor = Floor::from((false, &lines[..]));
+    let floor = DenseFloor::try_from((false, &lines[..]))?;
     println!(
         "Number of danger areas with diagonals: {}",
         floor.num_danger_areas()
     );
 
+    let floor = Floor::try_from((false, &lines[..]))?;
+    let max_density = floor.max_density();
+    println!(
+        "Highest density: {} ({} areas)",
+        max_density,
+        floor.count_with_density(max_density)
+    );
+
     Ok(())
 }
 
@@ -240,29 +290,136 @@ mod tests {
         assert_eq!(
             lines(),
             [
-                Line::new(Coordinate::new(0, 9), Coordinate::new(5, 9)),
-                Line::new(Coordinate::new(8, 0), Coordinate::new(0, 8)),
-                Line::new(Coordinate::new(9, 4), Coordinate::new(3, 4)),
-                Line::new(Coordinate::new(2, 2), Coordinate::new(2, 1)),
-                Line::new(Coordinate::new(7, 0), Coordinate::new(7, 4)),
-                Line::new(Coordinate::new(6, 4), Coordinate::new(2, 0)),
-                Line::new(Coordinate::new(0, 9), Coordinate::new(2, 9)),
-                Line::new(Coordinate::new(3, 4), Coordinate::new(1, 4)),
-                Line::new(Coordinate::new(0, 0), Coordinate::new(8, 8)),
-                Line::new(Coordinate::new(5, 5), Coordinate::new(8, 2)),
+                Line::new(Point::new(0, 9), Point::new(5, 9)),
+                Line::new(Point::new(8, 0), Point::new(0, 8)),
+                Line::new(Point::new(9, 4), Point::new(3, 4)),
+                Line::new(Point::new(2, 2), Point::new(2, 1)),
+                Line::new(Point::new(7, 0), Point::new(7, 4)),
+                Line::new(Point::new(6, 4), Point::new(2, 0)),
+                Line::new(Point::new(0, 9), Point::new(2, 9)),
+                Line::new(Point::new(3, 4), Point::new(1, 4)),
+                Line::new(Point::new(0, 0), Point::new(8, 8)),
+                Line::new(Point::new(5, 5), Point::new(8, 2)),
             ]
         );
     }
 
     #[test]
     fn part_1() {
-        let floor = Floor::from((true, &lines()[..]));
+        let floor = Floor::try_from((true, &lines()[..])).unwrap();
         assert_eq!(floor.num_danger_areas(), 5);
     }
 
     #[test]
     fn part_2() {
-        let floor = Floor::from((false, &lines()[..]));
+        let floor = Floor::try_from((false, &lines()[..])).unwrap();
         assert_eq!(floor.num_danger_areas(), 12);
     }
+
+    #[test]
+    fn dense_floor() {
+        for ignore_diagonals in [true, false] {
+            let floor = Floor::try_from((ignore_diagonals, &lines()[..])).unwrap();
+            let dense_floor = DenseFloor::try_from((ignore_diagonals, &lines()[..])).unwrap();
+            assert_eq!(dense_floor.num_danger_areas(), floor.num_danger_areas());
+        }
+        let dense_floor = DenseFloor::try_from((false, &lines()[..])).unwrap();
+        assert_eq!(dense_floor.density.len(), 100);
+        assert_eq!(dense_floor.num_danger_areas(), 12);
+    }
+
+    #[test]
+    fn dense_floor_negative() {
+        let lines: Vec<Line> = ["-3,4 -> 2,4", "0,-2 -> 0,6", "-1,3 -> 1,5"]
+            .iter()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        let floor = Floor::try_from((false, &lines[..])).unwrap();
+        let dense_floor = DenseFloor::try_from((false, &lines[..])).unwrap();
+        assert_eq!(dense_floor.density.len(), 6 * 9);
+        assert_eq!(dense_floor.num_danger_areas(), 1);
+        assert_eq!(dense_floor.num_danger_areas(), floor.num_danger_areas());
+    }
+
+    #[test]
+    fn dense_floor_high_density() {
+        let lines = vec!["1,1 -> 1,2".parse::<Line>().unwrap(); 70000];
+        let dense_floor = DenseFloor::try_from((false, &lines[..])).unwrap();
+        assert_eq!(dense_floor.density, [70000, 70000]);
+    }
+
+    #[test]
+    fn densities() {
+        let floor = Floor::try_from((false, &lines()[..])).unwrap();
+        assert_eq!(floor.count_with_density(0), 0);
+        assert_eq!(floor.count_with_density(1), 27);
+        assert_eq!(floor.count_with_density(2), 10);
+        assert_eq!(floor.count_with_density(3), 2);
+        assert_eq!(floor.max_density(), 3);
+
+        let floor = Floor::new(false);
+        assert_eq!(floor.max_density(), 0);
+    }
+
+    #[test]
+    fn coordinates() {
+        let line: Line = "2,2 -> 2,1".parse().unwrap();
+        assert_eq!(
+            line.coordinates().unwrap(),
+            [Point::new(2, 2), Point::new(2, 1)]
+        );
+        let line: Line = "5,5 -> 8,2".parse().unwrap();
+        assert_eq!(line.coordinates().unwrap().len(), 4);
+        let line: Line = "3,3 -> 3,3".parse().unwrap();
+        assert_eq!(line.coordinates().unwrap(), [Point::new(3, 3)]);
+    }
+
+    #[test]
+    fn slope() {
+        let line: Line = "1,1 -> 3,5".parse().unwrap();
+        assert!(line.is_diagonal());
+        assert_eq!(
+            line.coordinates().unwrap(),
+            [Point::new(1, 1), Point::new(2, 3), Point::new(3, 5)]
+        );
+        let line: Line = "6,1 -> 0,4".parse().unwrap();
+        assert_eq!(
+            line.coordinates().unwrap(),
+            [
+                Point::new(6, 1),
+                Point::new(4, 2),
+                Point::new(2, 3),
+                Point::new(0, 4)
+            ]
+        );
+
+        let line: Line = "0,0 -> 2,3".parse().unwrap();
+        let err = line.coordinates().unwrap_err();
+        assert_eq!(err.to_string(), "Unsupported line from 0,0 to 2,3");
+        assert!(Floor::try_from((false, &[line][..])).is_err());
+        assert!(Floor::try_from((true, &[line][..])).is_ok());
+    }
+
+    #[test]
+    fn display() {
+        let floor = Floor::try_from((false, &lines()[..])).unwrap();
+        assert_eq!(floor.bounding_box(), (Point::new(0, 0), Point::new(9, 9)));
+        assert_eq!(
+            floor.to_string(),
+            "1.1....11.\n\
+             .111...2..\n\
+             ..2.1.111.\n\
+             ...1.2.2..\n\
+             .112313211\n\
+             ...1.2....\n\
+             ..1...1...\n\
+             .1.....1..\n\
+             1.......1.\n\
+             222111....\n"
+        );
+
+        let floor = Floor::try_from((true, &lines()[..])).unwrap();
+        assert_eq!(floor.bounding_box(), (Point::new(0, 0), Point::new(9, 9)));
+        assert!(floor.to_string().starts_with(".......1..\n..1....1..\n"));
+    }
 }
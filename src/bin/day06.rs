@@ -1,7 +1,5 @@
-use advent_of_code_2021::Input;
-use itertools::Itertools;
+use advent_of_code_2021::{Answer, Input, Solution};
 use std::error;
-use std::str::FromStr;
 use thiserror::Error;
 
 /// Input parse error
@@ -10,60 +8,59 @@ use thiserror::Error;
 struct ParseError;
 
 /// Lanternfish population
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Population {
     /// Population count grouped by state. I.e. statecount[5] has the
     /// number of lanternfish with a state of 5
-    statecount: [usize; 9],
+    statecount: Vec<usize>,
+    /// State that lanternfish restart at after generating an offspring
+    reset: u8,
 }
 
 impl TryFrom<&[u8]> for Population {
     type Error = ParseError;
 
     fn try_from(states: &[u8]) -> Result<Self, Self::Error> {
-        let mut statecount = [0; 9];
-        for state in states {
-            if (0..9).contains(state) {
-                statecount[*state as usize] += 1;
-            } else {
-                return Err(ParseError);
-            }
-        }
-        Ok(Self { statecount })
+        let mut population = Self::new();
+        population.add(states)?;
+        Ok(population)
     }
 }
 
-impl FromStr for Population {
-    type Err = ParseError;
+impl Population {
+    /// Create new, empty population of lanternfish
+    fn new() -> Self {
+        Self::with_params(6, 8).expect("Lanternfish restart below newborn state")
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let states: Vec<u8> = s
-            .split(',')
-            .map(|s| s.parse())
-            .try_collect()
-            .map_err(|_| ParseError)?;
-        Self::try_from(&states[..])
+    /// Create new, empty population of creatures that restart at state
+    /// `reset` and whose offspring start at state `newborn`. Returns `None`
+    /// if the reset state is beyond the newborn state
+    fn with_params(reset: u8, newborn: u8) -> Option<Self> {
+        (reset <= newborn).then(|| Self {
+            statecount: vec![0; newborn as usize + 1],
+            reset,
+        })
+    }
+
+    /// Add creatures with the given states
+    fn add(&mut self, states: &[u8]) -> Result<(), ParseError> {
+        for state in states {
+            let count = self.statecount.get_mut(*state as usize).ok_or(ParseError)?;
+            *count += 1;
+        }
+        Ok(())
     }
-}
 
-impl Population {
     /// Evolve next day
     fn evolve(&mut self, days: usize) {
         for _ in 0..days {
             // Each day, all lanternfish decrease their state by 1. Lanternfish
-            // that reached 0, start at 6 again and generate an offspring that
-            // starts at 8
-            self.statecount = [
-                self.statecount[1],
-                self.statecount[2],
-                self.statecount[3],
-                self.statecount[4],
-                self.statecount[5],
-                self.statecount[6],
-                self.statecount[0] + self.statecount[7],
-                self.statecount[8],
-                self.statecount[0], // offspring
-            ];
+            // that reached 0, start at the reset state (6) again and generate
+            // an offspring that starts at the newborn state (8)
+            self.statecount.rotate_left(1);
+            let offspring = self.statecount[self.statecount.len() - 1];
+            self.statecount[self.reset as usize] += offspring;
         }
     }
 
@@ -71,14 +68,60 @@ impl Population {
     fn count(&self) -> usize {
         self.statecount.iter().sum()
     }
+
+    /// Number of lanternfish in the given state
+    #[allow(dead_code)]
+    fn count_in_state(&self, state: u8) -> usize {
+        self.statecount.get(state as usize).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct states that lanternfish are in
+    #[allow(dead_code)]
+    fn generations(&self) -> usize {
+        self.statecount.iter().filter(|count| **count > 0).count()
+    }
+
+    /// Factor by which the population grows in the given number of days
+    fn growth_factor(&self, days: usize) -> f64 {
+        let mut population = self.clone();
+        population.evolve(days);
+        population.count() as f64 / self.count() as f64
+    }
+}
+
+/// Day 6 solution
+#[allow(dead_code)]
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: usize = 6;
+
+    fn part1(mut input: Input) -> Answer {
+        let states: Vec<u8> = input.parsed_csv()?;
+        let mut population = Population::try_from(&states[..])?;
+        population.evolve(80);
+        Ok(population.count().to_string())
+    }
+
+    fn part2(mut input: Input) -> Answer {
+        let states: Vec<u8> = input.parsed_csv()?;
+        let mut population = Population::try_from(&states[..])?;
+        population.evolve(256);
+        Ok(population.count().to_string())
+    }
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
-    let line = Input::day(6)?.line()?;
-    let mut population: Population = line.parse()?;
+    let states: Vec<u8> = Input::day(6)?.parsed_csv()?;
+    let mut population = Population::try_from(&states[..])?;
 
     population.evolve(80);
     println!("Population after 80 days: {}", population.count());
+    println!(
+        "Growth factor for the next {} days: {:.2}",
+        256 - 80,
+        population.growth_factor(256 - 80)
+    );
 
     population.evolve(256 - 80);
     println!("Population after 256 days: {}", population.count());
@@ -106,4 +149,48 @@ mod tests {
         population.evolve(256 - 80);
         assert_eq!(population.count(), 26984457539);
     }
+
+    #[test]
+    fn params() {
+        let mut population = Population::with_params(5, 7).unwrap();
+        population.add(&INITIAL_STATE).unwrap();
+        population.evolve(3);
+        assert_eq!(population.count(), 7);
+        population.evolve(2);
+        assert_eq!(population.count(), 10);
+        population.evolve(5);
+        assert_eq!(population.count(), 15);
+        population.evolve(8);
+        assert_eq!(population.count(), 34);
+
+        let mut population = Population::with_params(2, 3).unwrap();
+        assert!(population.add(&[4]).is_err());
+
+        assert!(Population::with_params(9, 8).is_none());
+        let mut population = Population::with_params(3, 3).unwrap();
+        population.add(&[0, 3]).unwrap();
+        population.evolve(1);
+        assert_eq!(population.count_in_state(3), 2);
+        assert_eq!(population.count(), 3);
+    }
+
+    #[test]
+    fn states() {
+        let mut population = population();
+        assert_eq!(population.generations(), 4);
+        population.evolve(18);
+        // 6,0,6,4,5,6,0,1,1,2,6,0,1,1,1,2,2,3,3,4,6,7,8,8,8,8
+        let counts: Vec<_> = (0..9).map(|s| population.count_in_state(s)).collect();
+        assert_eq!(counts, [3, 5, 3, 2, 2, 1, 5, 1, 4]);
+        assert_eq!(population.count_in_state(9), 0);
+        assert_eq!(population.generations(), 9);
+        assert_eq!(population.growth_factor(80 - 18), 5934.0 / 26.0);
+        assert_eq!(population.count(), 26);
+
+        population.evolve(80 - 18);
+        let total: usize = (0..9).map(|s| population.count_in_state(s)).sum();
+        assert_eq!(total, 5934);
+        assert_eq!(population.generations(), 9);
+        assert_eq!(population.growth_factor(0), 1.0);
+    }
 }
@@ -1,21 +1,17 @@
 use advent_of_code_2021::Input;
-use itertools::Itertools;
 use std::error;
-use std::str::FromStr;
-use thiserror::Error;
-
-/// Input parse error
-#[derive(Debug, Error)]
-#[error("Input parse error")]
-struct ParseError;
 
 /// Fuel calculation model
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy)]
 enum FuelModel {
     /// Simple fuel model: 1 fuel per 1 distance
     Simple,
     /// Realistic fuel model: fuel increases per distance (1+2+3+4 for distance 4)
     Realistic,
+    /// Custom fuel model: fuel calculated by the given function (which should
+    /// be increasing with distance and convex)
+    #[allow(dead_code)]
+    Custom(fn(usize) -> usize),
 }
 
 impl FuelModel {
@@ -24,6 +20,7 @@ impl FuelModel {
         match self {
             FuelModel::Simple => distance,
             FuelModel::Realistic => (1 + distance) * distance / 2,
+            FuelModel::Custom(f) => f(distance),
         }
     }
 }
@@ -42,19 +39,6 @@ impl From<&[usize]> for Swarm {
     }
 }
 
-impl FromStr for Swarm {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let positions: Vec<usize> = s
-            .split(',')
-            .map(|s| s.parse())
-            .try_collect()
-            .map_err(|_| ParseError)?;
-        Ok(Self::from(&positions[..]))
-    }
-}
-
 impl Swarm {
     /// Find max (rightmost) position
     fn max_position(&self) -> usize {
@@ -66,21 +50,54 @@ impl Swarm {
         self.positions
             .iter()
             .copied()
-            .map(|pos| {
-                let distance = if pos > position {
-                    pos - position
-                } else {
-                    position - pos
-                };
-                model.fuel_for_distance(distance)
-            })
+            .map(|pos| model.fuel_for_distance(pos.abs_diff(position)))
             .sum()
     }
 
+    /// Median position (lower median for an even number of crabs)
+    fn median(&self) -> usize {
+        let mut positions = self.positions.clone();
+        positions.sort_unstable();
+        positions
+            .get(positions.len().saturating_sub(1) / 2)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Mean position
+    fn mean(&self) -> f64 {
+        self.positions.iter().sum::<usize>() as f64 / self.positions.len() as f64
+    }
+
     /// Calculate position with least fuel requirement
     fn least_fuel_required(&self, model: FuelModel) -> (usize, usize) {
-        // Brute-force find the least fuel requirement
-        (0..self.max_position())
+        // For the realistic model, the optimum is within 1/2 of the mean
+        if let FuelModel::Realistic = model {
+            if !self.positions.is_empty() {
+                let mean = self.mean();
+                return [mean.floor() as usize, mean.ceil() as usize]
+                    .into_iter()
+                    .map(|pos| (pos, self.fuel_required(pos, model)))
+                    .min_by_key(|(_pos, fuel)| *fuel)
+                    .unwrap_or((0, 0));
+            }
+        }
+        // The fuel requirement is convex in the position, so a ternary search
+        // narrows down the range containing the (leftmost) minimum
+        let (mut lo, mut hi) = (0, self.max_position());
+        while hi - lo > 2 {
+            let m1 = lo + (hi - lo) / 3;
+            let m2 = hi - (hi - lo) / 3;
+            let (fuel1, fuel2) = (self.fuel_required(m1, model), self.fuel_required(m2, model));
+            if fuel1 > fuel2 {
+                lo = m1 + 1;
+            } else if fuel1 < fuel2 {
+                hi = m2 - 1;
+            } else {
+                hi = m2;
+            }
+        }
+        (lo..=hi)
             .map(|pos| (pos, self.fuel_required(pos, model)))
             .min_by_key(|(_pos, fuel)| *fuel)
             .unwrap_or((0, 0))
@@ -88,8 +105,10 @@ impl Swarm {
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
-    let line = Input::day(7)?.line()?;
-    let swarm: Swarm = line.parse()?;
+    let positions: Vec<usize> = Input::day(7)?.parsed_csv()?;
+    let swarm = Swarm::from(&positions[..]);
+
+    println!("Median: {}, mean: {:.2}", swarm.median(), swarm.mean());
 
     let (position, fuel) = swarm.least_fuel_required(FuelModel::Simple);
     println!("Aligning at {} uses least fuel: {}", position, fuel);
@@ -139,4 +158,61 @@ mod tests {
         assert_eq!(swarm.fuel_required(5, FuelModel::Realistic), 168);
         assert_eq!(swarm.least_fuel_required(FuelModel::Realistic), (5, 168));
     }
+
+    #[test]
+    fn median_mean() {
+        let swarm = swarm();
+        assert_eq!(swarm.median(), 2);
+        assert_eq!(swarm.mean().round(), 5.0);
+        assert_eq!(
+            swarm.least_fuel_required(FuelModel::Simple).0,
+            swarm.median()
+        );
+    }
+
+    #[test]
+    fn custom_model() {
+        let swarm = swarm();
+        let model = FuelModel::Custom(|distance| distance * distance);
+        assert_eq!(model.fuel_for_distance(4), 16);
+        assert_eq!(swarm.fuel_required(2, model), 375);
+        assert_eq!(swarm.least_fuel_required(model), (5, 291));
+
+        let model = FuelModel::Custom(|distance| distance);
+        assert_eq!(
+            swarm.least_fuel_required(model),
+            swarm.least_fuel_required(FuelModel::Simple)
+        );
+    }
+
+    #[test]
+    fn ternary_search() {
+        fn brute_force(swarm: &Swarm, model: FuelModel) -> (usize, usize) {
+            (0..=swarm.max_position())
+                .map(|pos| (pos, swarm.fuel_required(pos, model)))
+                .min_by_key(|(_pos, fuel)| *fuel)
+                .unwrap()
+        }
+
+        let mut seed: u64 = 42;
+        let mut random = |n: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ((seed >> 33) % n) as usize
+        };
+        for _ in 0..200 {
+            let len = random(20) + 1;
+            let range = random(50) as u64 + 1;
+            let positions: Vec<usize> = (0..len).map(|_| random(range)).collect();
+            let swarm = Swarm::from(&positions[..]);
+            for model in [FuelModel::Simple, FuelModel::Realistic] {
+                assert_eq!(
+                    swarm.least_fuel_required(model),
+                    brute_force(&swarm, model),
+                    "{:?} {:?}",
+                    positions,
+                    model
+                );
+            }
+        }
+    }
 }
@@ -2,6 +2,7 @@ use advent_of_code_2021::Input;
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::error;
+use std::fmt;
 use std::str::FromStr;
 use thiserror::Error;
 
@@ -11,7 +12,7 @@ use thiserror::Error;
 struct ParseError;
 
 /// A segment of a 7-segment digit
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Segment {
     A,
     B,
@@ -22,6 +23,19 @@ enum Segment {
     G,
 }
 
+impl Segment {
+    /// All segments
+    const ALL: [Segment; 7] = [
+        Segment::A,
+        Segment::B,
+        Segment::C,
+        Segment::D,
+        Segment::E,
+        Segment::F,
+        Segment::G,
+    ];
+}
+
 impl TryFrom<char> for Segment {
     type Error = ParseError;
 
@@ -39,6 +53,11 @@ impl TryFrom<char> for Segment {
     }
 }
 
+/// Segments of numbers `0` to `9` with standard wiring
+const NUMBER_SEGMENTS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
 /// A 7-segment digit
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Digit(HashSet<Segment>);
@@ -51,7 +70,46 @@ impl FromStr for Digit {
     }
 }
 
+impl fmt::Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seg = |segment, ch| {
+            if self.0.contains(&segment) {
+                ch
+            } else {
+                ' '
+            }
+        };
+        writeln!(f, " {} ", seg(Segment::A, '_'))?;
+        writeln!(
+            f,
+            "{}{}{}",
+            seg(Segment::B, '|'),
+            seg(Segment::G, '_'),
+            seg(Segment::C, '|')
+        )?;
+        write!(
+            f,
+            "{}{}{}",
+            seg(Segment::E, '|'),
+            seg(Segment::D, '_'),
+            seg(Segment::F, '|')
+        )
+    }
+}
+
 impl Digit {
+    /// Digit of the given number with standard wiring
+    #[allow(dead_code)]
+    fn from_number(n: u8) -> Option<Self> {
+        NUMBER_SEGMENTS.get(n as usize)?.parse().ok()
+    }
+
+    /// Active segments, sorted
+    #[allow(dead_code)]
+    fn segments(&self) -> Vec<Segment> {
+        self.0.iter().copied().sorted().collect()
+    }
+
     /// Detect `1`: it's unique by having exactly 2 segments active
     fn is_one(&self) -> bool {
         self.0.len() == 2
@@ -68,6 +126,11 @@ impl Digit {
         [2, 4, 3, 7].contains(&self.0.len())
     }
 
+    /// Digit with each segment rewired by the given function
+    fn map(&self, f: impl Fn(Segment) -> Segment) -> Self {
+        Self(self.0.iter().map(|segment| f(*segment)).collect())
+    }
+
     /// Determine number of segments that overlap with the given other digit
     fn overlap(&self, other: &Self) -> usize {
         (&self.0 & &other.0).len()
@@ -145,6 +208,33 @@ impl Entry {
             .sum()
     }
 
+    /// Determine numbers of digits by trying all possible segment wirings
+    /// until all patterns map to valid numbers. Slow, but works without
+    /// relying on `1` and `4` being present
+    #[allow(dead_code)]
+    fn decode_by_permutation(&self) -> Option<[u8; 4]> {
+        let numbers: Vec<Digit> = NUMBER_SEGMENTS.iter().map(|s| s.parse().unwrap()).collect();
+        Segment::ALL.into_iter().permutations(7).find_map(|wiring| {
+            let number = |digit: &Digit| {
+                let digit = digit.map(|segment| wiring[segment as usize]);
+                numbers.iter().position(|n| *n == digit).map(|n| n as u8)
+            };
+            if !self
+                .patterns
+                .iter()
+                .all(|pattern| number(pattern).is_some())
+            {
+                return None;
+            }
+            Some([
+                number(&self.digits[0])?,
+                number(&self.digits[1])?,
+                number(&self.digits[2])?,
+                number(&self.digits[3])?,
+            ])
+        })
+    }
+
     /// Determine value of digits
     fn value(&self) -> Option<usize> {
         let one = self.one()?;
@@ -166,12 +256,7 @@ fn count_simple_number_digits(entries: &[Entry]) -> usize {
 }
 
 fn sum_of_values(entries: &[Entry]) -> Option<usize> {
-    entries
-        .iter()
-        .fold(Some(0), |sum, entry| match (sum, entry.value()) {
-            (Some(sum), Some(value)) => Some(sum + value),
-            _ => None,
-        })
+    entries.iter().map(Entry::value).sum()
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
@@ -245,4 +330,49 @@ mod tests {
         assert_eq!(entries[9].value(), Some(4315));
         assert_eq!(sum_of_values(&entries), Some(61229));
     }
+
+    #[test]
+    fn encode() {
+        assert_eq!(
+            Digit::from_number(7).unwrap().segments(),
+            [Segment::A, Segment::C, Segment::F]
+        );
+        assert_eq!(Digit::from_number(8).unwrap().segments(), Segment::ALL);
+        assert_eq!(Digit::from_number(10), None);
+
+        let one = Digit::from_number(1).unwrap();
+        let four = Digit::from_number(4).unwrap();
+        for n in 0..10 {
+            let digit = Digit::from_number(n).unwrap();
+            assert_eq!(digit.number(&one, &four), Some(n));
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Digit::from_number(1).unwrap().to_string(), "   \n  |\n  |");
+        assert_eq!(Digit::from_number(8).unwrap().to_string(), " _ \n|_|\n|_|");
+        assert_eq!(Digit::from_number(5).unwrap().to_string(), " _ \n|_ \n _|");
+        assert_eq!(Digit(HashSet::new()).to_string(), "   \n   \n   ");
+    }
+
+    #[test]
+    fn permutation() {
+        let entry = entry();
+        assert_eq!(entry.decode_by_permutation(), Some([5, 3, 5, 3]));
+
+        for entry in entries() {
+            let [a, b, c, d] = entry.decode_by_permutation().unwrap();
+            let value = a as usize * 1000 + b as usize * 100 + c as usize * 10 + d as usize;
+            assert_eq!(Some(value), entry.value());
+        }
+
+        // Pattern for `1` replaced by another `7`
+        let entry: Entry =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb dab | cdfeb fcadb cdfeb cdbaf"
+                .parse()
+                .unwrap();
+        assert_eq!(entry.value(), None);
+        assert_eq!(entry.decode_by_permutation(), Some([5, 3, 5, 3]));
+    }
 }
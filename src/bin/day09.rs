@@ -1,4 +1,4 @@
-use advent_of_code_2021::Input;
+use advent_of_code_2021::{Grid, Input};
 use itertools::Itertools;
 use std::collections::HashSet;
 use std::error;
@@ -11,62 +11,63 @@ struct ParseError;
 
 /// Floor height map
 #[derive(Debug)]
-struct HeightMap(Vec<Vec<u8>>);
+struct HeightMap {
+    heights: Grid<u8>,
+    diagonal_adjacency: bool,
+}
+
+impl From<Grid<u8>> for HeightMap {
+    fn from(heights: Grid<u8>) -> Self {
+        Self {
+            heights,
+            diagonal_adjacency: false,
+        }
+    }
+}
 
 impl<S: AsRef<str>> TryFrom<&[S]> for HeightMap {
     type Error = ParseError;
 
     fn try_from(heightmap: &[S]) -> Result<Self, Self::Error> {
-        Ok(Self(
-            heightmap
-                .iter()
-                .map(|line| {
-                    line.as_ref()
-                        .chars()
-                        .map(|ch| {
-                            ch.to_digit(10)
-                                .ok_or(ParseError)
-                                .and_then(|n| u8::try_from(n).map_err(|_| ParseError))
-                        })
-                        .try_collect()
-                })
-                .try_collect()?,
-        ))
+        let heights: Grid<u8> = heightmap
+            .iter()
+            .map(AsRef::as_ref)
+            .join("\n")
+            .parse()
+            .map_err(|_| ParseError)?;
+        Ok(Self::from(heights))
     }
 }
 
 impl HeightMap {
+    /// Also consider diagonal positions as adjacent
+    #[allow(dead_code)]
+    fn with_diagonal_adjacency(mut self, diagonal_adjacency: bool) -> Self {
+        self.diagonal_adjacency = diagonal_adjacency;
+        self
+    }
+
     /// Get height at a given position if exists
     fn get(&self, x: usize, y: usize) -> Option<u8> {
-        self.0.get(y).and_then(|row| row.get(x).copied())
+        self.heights.get(x, y).copied()
     }
 
     /// Check whether the given position is a low point (i.e. there's no adjacent lower point)
     fn is_low_point(&self, x: usize, y: usize) -> Option<bool> {
         let height = self.get(x, y)?;
-        let left = (x > 0).then(|| self.get(x - 1, y)).flatten();
-        let right = self.get(x + 1, y);
-        let above = (y > 0).then(|| self.get(x, y - 1)).flatten();
-        let below = self.get(x, y + 1);
         Some(
-            [left, right, above, below]
-                .iter()
-                .map(|adjacent| adjacent.map(|h| h <= height).unwrap_or(false))
-                .all(|is_lower| !is_lower),
+            self.neighbors(x, y)
+                .filter_map(|(x, y)| self.get(x, y))
+                .all(|h| h > height),
         )
     }
 
     /// Get all low points
     fn low_points(&self) -> Vec<(usize, usize)> {
-        let mut points = Vec::new();
-        for y in 0..self.0.len() {
-            for x in 0..self.0[y].len() {
-                if self.is_low_point(x, y).unwrap_or(false) {
-                    points.push((x, y));
-                }
-            }
-        }
-        points
+        self.heights
+            .positions()
+            .filter(|&(x, y)| self.is_low_point(x, y).unwrap_or(false))
+            .collect()
     }
 
     /// Get risk sum of all low points
@@ -80,51 +81,69 @@ impl HeightMap {
             .sum()
     }
 
+    /// Get adjacent positions of the given position that are within the map
+    fn neighbors(&self, x: usize, y: usize) -> Box<dyn Iterator<Item = (usize, usize)>> {
+        if self.diagonal_adjacency {
+            Box::new(self.heights.neighbors8(x, y))
+        } else {
+            Box::new(self.heights.neighbors4(x, y))
+        }
+    }
+
     /// Get all points of basin at the given point
+    #[allow(dead_code)]
     fn basin_points(&self, x: usize, y: usize) -> HashSet<(usize, usize)> {
-        fn recurse(
-            heightmap: &HeightMap,
-            points: &mut HashSet<(usize, usize)>,
-            x: usize,
-            y: usize,
-        ) {
-            if !points.contains(&(x, y)) {
-                if let Some(height) = heightmap.get(x, y) {
-                    if height < 9 {
-                        points.insert((x, y));
-                        if x > 0 {
-                            recurse(heightmap, points, x - 1, y);
-                        }
-                        if y > 0 {
-                            recurse(heightmap, points, x, y - 1);
-                        }
-                        recurse(heightmap, points, x + 1, y);
-                        recurse(heightmap, points, x, y + 1);
-                    }
+        let mut points = HashSet::new();
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if points.contains(&(x, y)) {
+                continue;
+            }
+            if let Some(height) = self.get(x, y) {
+                if height < 9 {
+                    points.insert((x, y));
+                    stack.extend(self.neighbors(x, y));
                 }
             }
         }
-
-        let mut points = HashSet::new();
-        recurse(self, &mut points, x, y);
         points
     }
 
+    /// Label every point with the id of the basin it belongs to (points of
+    /// height 9 don't belong to any basin), return labels and number of basins
+    fn label_basins(&self) -> (Grid<Option<usize>>, usize) {
+        let mut labels = Grid::new(self.heights.width(), self.heights.height(), None);
+        let mut count = 0;
+        for (x, y) in self.heights.positions() {
+            if self.heights[(x, y)] >= 9 || labels[(x, y)].is_some() {
+                continue;
+            }
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                if self.heights[(x, y)] < 9 && labels[(x, y)].is_none() {
+                    labels[(x, y)] = Some(count);
+                    stack.extend(self.neighbors(x, y));
+                }
+            }
+            count += 1;
+        }
+        (labels, count)
+    }
+
     /// Multiply size of top 3 basin sizes
     fn top_basins_size_factor(&self) -> usize {
-        let mut basin_sizes: Vec<_> = self
-            .low_points()
-            .iter()
-            .map(|(x, y)| self.basin_points(*x, *y).len())
-            .collect();
+        let (labels, count) = self.label_basins();
+        let mut basin_sizes = vec![0; count];
+        for label in labels.positions().filter_map(|(x, y)| labels[(x, y)]) {
+            basin_sizes[label] += 1;
+        }
         basin_sizes.sort_by(|a, b| b.cmp(a));
         basin_sizes.iter().take(3).product()
     }
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
-    let lines: Vec<_> = Input::day(9)?.lines().try_collect()?;
-    let heightmap = HeightMap::try_from(&lines[..])?;
+    let heightmap = HeightMap::from(Grid::try_from(Input::day(9)?.digit_grid()?)?);
 
     println!(
         "Low points total risk: {}",
@@ -171,4 +190,49 @@ mod tests {
         assert_eq!(heightmap.basin_points(6, 4).len(), 9);
         assert_eq!(heightmap.top_basins_size_factor(), 1134);
     }
+
+    #[test]
+    fn labels() {
+        let heightmap = heightmap();
+        let (labels, count) = heightmap.label_basins();
+        assert_eq!(count, 4);
+        assert_eq!(labels[(0, 0)], Some(0));
+        assert_eq!(labels[(1, 0)], Some(0));
+        assert_eq!(labels[(2, 0)], None);
+        assert_eq!(labels[(9, 0)], Some(1));
+        assert_eq!(labels[(2, 2)], Some(2));
+        assert_eq!(labels[(6, 4)], Some(3));
+        assert_eq!(heightmap.top_basins_size_factor(), 1134);
+
+        // Basin without a unique low point
+        let heightmap = HeightMap::try_from(&["1191", "1999", "9922"][..]).unwrap();
+        assert_eq!(heightmap.low_points(), [(3, 0)]);
+        assert_eq!(heightmap.label_basins().1, 3);
+        assert_eq!(heightmap.top_basins_size_factor(), 3 * 2);
+    }
+
+    #[test]
+    fn diagonal_adjacency() {
+        let heightmap = heightmap().with_diagonal_adjacency(true);
+        assert_eq!(heightmap.low_points(), [(1, 0), (9, 0), (2, 2), (6, 4)]);
+        assert_eq!(heightmap.neighbors(0, 0).count(), 3);
+        assert_eq!(heightmap.neighbors(1, 1).count(), 8);
+        assert_eq!(heightmap.neighbors(9, 4).count(), 3);
+
+        let heightmap = HeightMap::try_from(&["19", "91"][..]).unwrap();
+        assert_eq!(heightmap.low_points(), [(0, 0), (1, 1)]);
+        assert_eq!(heightmap.basin_points(0, 0).len(), 1);
+        let heightmap = heightmap.with_diagonal_adjacency(true);
+        assert_eq!(heightmap.low_points(), []);
+        assert_eq!(heightmap.basin_points(0, 0).len(), 2);
+        assert_eq!(heightmap.label_basins().1, 1);
+    }
+
+    #[test]
+    fn large_basin() {
+        let heightmap = HeightMap::from(Grid::new(500, 500, 0));
+        assert_eq!(heightmap.basin_points(0, 0).len(), 500 * 500);
+        let heightmap = HeightMap::from(Grid::new(500, 1, 0));
+        assert_eq!(heightmap.basin_points(250, 0).len(), 500);
+    }
 }
@@ -14,66 +14,150 @@ enum ParseError {
     Syntax,
 }
 
-fn parse(line: &str) -> Result<(), ParseError> {
-    let mut chunks = Vec::new();
-
-    for token in line.chars() {
-        match token {
-            '(' => chunks.push(')'),
-            '[' => chunks.push(']'),
-            '{' => chunks.push('}'),
-            '<' => chunks.push('>'),
-            ')' | ']' | '}' | '>' => {
+/// Bracket pairs and their syntax error scores
+#[derive(Debug, Clone)]
+struct Brackets {
+    /// Opening and closing character of each pair
+    pairs: Vec<(char, char)>,
+    /// Corrupt and incomplete score of each pair
+    scores: Vec<(usize, usize)>,
+}
+
+impl Default for Brackets {
+    fn default() -> Self {
+        Self::new(&[
+            ('(', ')', 3, 1),
+            ('[', ']', 57, 2),
+            ('{', '}', 1197, 3),
+            ('<', '>', 25137, 4),
+        ])
+    }
+}
+
+impl Brackets {
+    /// Create bracket pairs from opening and closing character, corrupt score
+    /// and incomplete score
+    fn new(pairs: &[(char, char, usize, usize)]) -> Self {
+        Self {
+            pairs: pairs
+                .iter()
+                .map(|(open, close, _, _)| (*open, *close))
+                .collect(),
+            scores: pairs.iter().map(|(_, _, cs, is)| (*cs, *is)).collect(),
+        }
+    }
+
+    /// Scores of the pair with the given closing character
+    fn scores(&self, close: char) -> Option<(usize, usize)> {
+        let i = self.pairs.iter().position(|(_, c)| *c == close)?;
+        Some(self.scores[i])
+    }
+
+    fn parse(&self, line: &str) -> Result<(), ParseError> {
+        let mut chunks = Vec::new();
+
+        for token in line.chars() {
+            if let Some((_, close)) = self.pairs.iter().find(|(open, _)| *open == token) {
+                chunks.push(*close);
+            } else if self.pairs.iter().any(|(_, close)| *close == token) {
                 let expected = chunks.pop().ok_or(ParseError::Syntax)?;
                 if token != expected {
                     return Err(ParseError::Corrupted(expected, token));
                 }
+            } else {
+                return Err(ParseError::Syntax);
             }
-            _ => return Err(ParseError::Syntax),
+        }
+
+        if !chunks.is_empty() {
+            Err(ParseError::Incomplete(chunks))
+        } else {
+            Ok(())
         }
     }
 
-    if !chunks.is_empty() {
-        Err(ParseError::Incomplete(chunks))
-    } else {
-        Ok(())
+    /// Parse the given lines lazily
+    fn analyze<'a, S: AsRef<str>>(
+        &'a self,
+        lines: &'a [S],
+    ) -> impl Iterator<Item = Result<(), ParseError>> + 'a {
+        lines.iter().map(|line| self.parse(line.as_ref()))
     }
+
+    fn corrupt_score(&self, line: &str) -> usize {
+        self.corrupt_score_of(&self.parse(line))
+    }
+
+    /// Corrupt score of an already parsed line
+    fn corrupt_score_of(&self, result: &Result<(), ParseError>) -> usize {
+        match result {
+            Err(ParseError::Corrupted(_, ch)) => self.scores(*ch).map_or(0, |(score, _)| score),
+            _ => 0,
+        }
+    }
+
+    fn completion(&self, line: &str) -> Option<String> {
+        Self::completion_of(&self.parse(line))
+    }
+
+    /// Closing characters that complete an already parsed line
+    fn completion_of(result: &Result<(), ParseError>) -> Option<String> {
+        match result {
+            Err(ParseError::Incomplete(chunks)) => Some(chunks.iter().rev().collect()),
+            _ => None,
+        }
+    }
+
+    /// Incomplete score of the given closing characters
+    fn completion_score(&self, completion: &str) -> usize {
+        completion.chars().fold(0, |score, ch| {
+            score * 5 + self.scores(ch).map_or(0, |(_, score)| score)
+        })
+    }
+
+    fn incomplete_score(&self, line: &str) -> usize {
+        self.incomplete_score_of(&self.parse(line))
+    }
+
+    /// Incomplete score of an already parsed line
+    fn incomplete_score_of(&self, result: &Result<(), ParseError>) -> usize {
+        Self::completion_of(result).map_or(0, |completion| self.completion_score(&completion))
+    }
+}
+
+#[allow(dead_code)]
+fn parse(line: &str) -> Result<(), ParseError> {
+    Brackets::default().parse(line)
 }
 
+#[allow(dead_code)]
 fn corrupt_score(line: &str) -> usize {
-    match parse(line) {
-        Err(ParseError::Corrupted(_, ')')) => 3,
-        Err(ParseError::Corrupted(_, ']')) => 57,
-        Err(ParseError::Corrupted(_, '}')) => 1197,
-        Err(ParseError::Corrupted(_, '>')) => 25137,
-        _ => 0,
-    }
+    Brackets::default().corrupt_score(line)
+}
+
+#[allow(dead_code)]
+fn completion(line: &str) -> Option<String> {
+    Brackets::default().completion(line)
 }
 
+#[allow(dead_code)]
 fn incomplete_score(line: &str) -> usize {
-    match parse(line) {
-        Err(ParseError::Incomplete(chunks)) => chunks.iter().rev().fold(0, |score, ch| {
-            score * 5
-                + match ch {
-                    ')' => 1,
-                    ']' => 2,
-                    '}' => 3,
-                    '>' => 4,
-                    _ => 0,
-                }
-        }),
-        _ => 0,
-    }
+    Brackets::default().incomplete_score(line)
 }
 
 fn total_corrupt_score<S: AsRef<str>>(lines: &[S]) -> usize {
-    lines.iter().map(|line| corrupt_score(line.as_ref())).sum()
+    let brackets = Brackets::default();
+    brackets
+        .analyze(lines)
+        .map(|result| brackets.corrupt_score_of(&result))
+        .sum()
 }
 
 fn median_incomplete_score<S: AsRef<str>>(lines: &[S]) -> usize {
-    let mut scores: Vec<_> = lines
-        .iter()
-        .map(|line| incomplete_score(line.as_ref()))
+    let brackets = Brackets::default();
+    let mut scores: Vec<_> = brackets
+        .analyze(lines)
+        .map(|result| brackets.incomplete_score_of(&result))
         .filter(|score| *score > 0)
         .collect();
     scores.sort_unstable();
@@ -161,4 +245,71 @@ mod tests {
 
         assert_eq!(median_incomplete_score(&EXAMPLES), 288957);
     }
+
+    #[test]
+    fn analyze_lines() {
+        let (mut corrupted, mut incomplete, mut other) = (0, 0, 0);
+        for result in Brackets::default().analyze(&EXAMPLES) {
+            match result {
+                Err(ParseError::Corrupted(_, _)) => corrupted += 1,
+                Err(ParseError::Incomplete(_)) => incomplete += 1,
+                _ => other += 1,
+            }
+        }
+        assert_eq!((corrupted, incomplete, other), (5, 5, 0));
+
+        let results: Vec<_> = Brackets::default()
+            .analyze(&["()", ")", "(]", "(x"])
+            .collect();
+        assert_eq!(
+            results,
+            [
+                Ok(()),
+                Err(ParseError::Syntax),
+                Err(ParseError::Corrupted(')', ']')),
+                Err(ParseError::Syntax),
+            ]
+        );
+    }
+
+    #[test]
+    fn completions() {
+        assert_eq!(completion(EXAMPLES[0]).unwrap(), "}}]])})]");
+        assert_eq!(completion(EXAMPLES[1]).unwrap(), ")}>]})");
+        assert_eq!(completion(EXAMPLES[9]).unwrap(), "])}>");
+        assert_eq!(completion(EXAMPLES[2]), None);
+        assert_eq!(completion("[<>]"), None);
+
+        let brackets = Brackets::default();
+        for line in EXAMPLES {
+            assert_eq!(
+                completion(line).map_or(0, |s| brackets.completion_score(&s)),
+                incomplete_score(line)
+            );
+        }
+        assert_eq!(brackets.completion_score("])}>"), 294);
+        assert_eq!(brackets.completion_score(""), 0);
+    }
+
+    #[test]
+    fn custom_brackets() {
+        let brackets = Brackets::new(&[('«', '»', 10, 1), ('(', ')', 20, 2)]);
+        assert_eq!(brackets.parse("«()»"), Ok(()));
+        assert_eq!(brackets.parse("«(»"), Err(ParseError::Corrupted(')', '»')));
+        assert_eq!(brackets.parse("«[]»"), Err(ParseError::Syntax));
+        assert_eq!(brackets.corrupt_score("«(»"), 10);
+        assert_eq!(brackets.corrupt_score("(«)"), 20);
+        assert_eq!(brackets.completion("««(").unwrap(), ")»»");
+        assert_eq!(brackets.incomplete_score("««("), (2 * 5 + 1) * 5 + 1);
+
+        let results: Vec<_> = brackets.analyze(&["«()»", "«(", "()]"]).collect();
+        assert_eq!(
+            results,
+            [
+                Ok(()),
+                Err(ParseError::Incomplete(vec!['»', ')'])),
+                Err(ParseError::Syntax),
+            ]
+        );
+    }
 }
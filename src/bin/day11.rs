@@ -1,6 +1,7 @@
 use advent_of_code_2021::Input;
 use itertools::Itertools;
 use std::error;
+use std::fmt;
 use thiserror::Error;
 
 /// Input parse error
@@ -10,76 +11,104 @@ struct ParseError;
 
 /// Grid of dumb octopuses
 #[derive(Debug)]
-struct Grid(Vec<Vec<u8>>);
+struct Grid {
+    cells: Vec<Vec<u8>>,
+    /// Energy level at which an octopus flashes
+    flash_threshold: u8,
+}
+
+impl From<Vec<Vec<u8>>> for Grid {
+    fn from(cells: Vec<Vec<u8>>) -> Self {
+        Self {
+            cells,
+            flash_threshold: 10,
+        }
+    }
+}
 
 impl<S: AsRef<str>> TryFrom<&[S]> for Grid {
     type Error = ParseError;
 
     fn try_from(heightmap: &[S]) -> Result<Self, Self::Error> {
-        Ok(Self(
-            heightmap
-                .iter()
-                .map(|line| {
-                    line.as_ref()
-                        .chars()
-                        .map(|ch| {
-                            ch.to_digit(10)
-                                .ok_or(ParseError)
-                                .and_then(|n| u8::try_from(n).map_err(|_| ParseError))
-                        })
-                        .try_collect()
-                })
-                .try_collect()?,
-        ))
+        let cells: Vec<Vec<u8>> = heightmap
+            .iter()
+            .map(|line| {
+                line.as_ref()
+                    .chars()
+                    .map(|ch| {
+                        ch.to_digit(10)
+                            .ok_or(ParseError)
+                            .and_then(|n| u8::try_from(n).map_err(|_| ParseError))
+                    })
+                    .try_collect()
+            })
+            .try_collect()?;
+        Ok(Self::from(cells))
     }
 }
 
 impl Grid {
-    /// Increase energy level of given cell
-    fn increase(&mut self, x: usize, y: usize) {
-        if let Some(cell) = self.0.get_mut(y).and_then(|row| row.get_mut(x)) {
-            *cell += 1;
-            // If cell was just triggered to flash, increase adjacent cells as well
-            if *cell == 10 {
-                if x > 0 && y > 0 {
-                    self.increase(x - 1, y - 1);
-                }
-                if x > 0 {
-                    self.increase(x - 1, y);
-                    self.increase(x - 1, y + 1);
-                }
-                if y > 0 {
-                    self.increase(x, y - 1);
-                    self.increase(x + 1, y - 1);
-                }
-                self.increase(x, y + 1);
-                self.increase(x + 1, y);
-                self.increase(x + 1, y + 1);
-            }
-        }
+    /// Use the given energy level at which octopuses flash
+    #[allow(dead_code)]
+    fn with_flash_threshold(mut self, flash_threshold: u8) -> Self {
+        self.flash_threshold = flash_threshold;
+        self
+    }
+
+    /// Total number of octopuses
+    fn len(&self) -> usize {
+        self.cells.iter().map(Vec::len).sum()
+    }
+
+    /// Get adjacent positions (including diagonals) of the given position
+    fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (y.saturating_sub(1)..=y + 1)
+            .cartesian_product(x.saturating_sub(1)..=x + 1)
+            .filter(move |(ny, nx)| (*nx, *ny) != (x, y))
+            .filter(|(ny, nx)| self.cells.get(*ny).is_some_and(|row| *nx < row.len()))
+            .map(|(ny, nx)| (nx, ny))
     }
 
     /// Do one step, return number of flashes
     fn step(&mut self) -> usize {
-        // Increase energy of all cells
-        for y in 0..self.0.len() {
-            for x in 0..self.0[y].len() {
-                self.increase(x, y);
+        self.step_detailed().0
+    }
+
+    /// Do one step, return number of flashes and positions that flashed
+    fn step_detailed(&mut self) -> (usize, Vec<(usize, usize)>) {
+        // Increase energy of all cells, remember cells that were just
+        // triggered to flash
+        let mut queue = Vec::new();
+        for y in 0..self.cells.len() {
+            for x in 0..self.cells[y].len() {
+                self.cells[y][x] += 1;
+                if self.cells[y][x] == self.flash_threshold {
+                    queue.push((x, y));
+                }
+            }
+        }
+        // Flashing cells increase adjacent cells as well, which may trigger
+        // them to flash
+        while let Some((x, y)) = queue.pop() {
+            let neighbors: Vec<_> = self.neighbors(x, y).collect();
+            for (nx, ny) in neighbors {
+                self.cells[ny][nx] += 1;
+                if self.cells[ny][nx] == self.flash_threshold {
+                    queue.push((nx, ny));
+                }
             }
         }
         // Flash all overloaded cells
-        let mut flashes = 0;
-        for y in 0..self.0.len() {
-            for x in 0..self.0[y].len() {
-                if let Some(cell) = self.0.get_mut(y).and_then(|row| row.get_mut(x)) {
-                    if *cell >= 10 {
-                        *cell = 0;
-                        flashes += 1;
-                    }
+        let mut flashed = Vec::new();
+        for (y, row) in self.cells.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if *cell >= self.flash_threshold {
+                    *cell = 0;
+                    flashed.push((x, y));
                 }
             }
         }
-        flashes
+        (flashed.len(), flashed)
     }
 
     /// Do one step, return number of flashes
@@ -89,24 +118,37 @@ impl Grid {
 
     /// Step until all octopuses flash, return number of steps
     fn step_until_full_flash(&mut self) -> usize {
+        let len = self.len();
         let mut steps = 0;
         loop {
             steps += 1;
-            if self.step() == 100 {
+            if self.step() == len {
                 return steps;
             }
         }
     }
 }
 
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.cells {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 fn main() -> Result<(), Box<dyn error::Error>> {
-    let lines: Vec<_> = Input::day(11)?.lines().try_collect()?;
+    let energy_levels = Input::day(11)?.digit_grid()?;
 
-    let mut grid = Grid::try_from(&lines[..])?;
+    let mut grid = Grid::from(energy_levels.clone());
     let flashes = grid.steps(100);
     println!("Total flashes after 100 steps: {}", flashes);
 
-    let mut grid = Grid::try_from(&lines[..])?;
+    let mut grid = Grid::from(energy_levels);
     let steps = grid.step_until_full_flash();
     println!("Steps until full flash: {}", steps);
 
@@ -167,6 +209,36 @@ mod tests {
         assert_eq!(grid.step(), 13);
     }
 
+    #[test]
+    fn flashed_positions() {
+        const STEP_2: &str = "\
+            8807476555\n\
+            5089087054\n\
+            8597889608\n\
+            8485769600\n\
+            8700908800\n\
+            6600088989\n\
+            6800005943\n\
+            0000007456\n\
+            9000000876\n\
+            8700006848\n";
+
+        let mut grid = grid();
+        assert_eq!(grid.to_string(), GRID.join("\n") + "\n");
+        assert_eq!(grid.step_detailed(), (0, vec![]));
+        let (flashes, flashed) = grid.step_detailed();
+        assert_eq!(flashes, 35);
+        assert_eq!(flashed.len(), 35);
+        assert_eq!(grid.to_string(), STEP_2);
+        let expected: Vec<_> = STEP_2
+            .lines()
+            .enumerate()
+            .flat_map(|(y, line)| line.match_indices('0').map(move |(x, _)| (x, y)))
+            .collect();
+        assert_eq!(flashed, expected);
+        assert_eq!(flashed[..3], [(2, 0), (1, 1), (4, 1)]);
+    }
+
     #[test]
     fn part_1b() {
         let mut grid = grid();
@@ -178,4 +250,30 @@ mod tests {
         let mut grid = grid();
         assert_eq!(grid.step_until_full_flash(), 195);
     }
+
+    #[test]
+    fn small_grid() {
+        let mut grid = Grid::try_from(&["999", "999", "999"][..]).unwrap();
+        assert_eq!(grid.len(), 9);
+        assert_eq!(grid.step_until_full_flash(), 1);
+
+        let mut grid = Grid::try_from(&["000", "000", "000"][..]).unwrap();
+        assert_eq!(grid.step_until_full_flash(), 10);
+
+        let mut grid = Grid::try_from(&["000", "000", "000"][..])
+            .unwrap()
+            .with_flash_threshold(5);
+        assert_eq!(grid.step_until_full_flash(), 5);
+    }
+
+    #[test]
+    fn large_grid() {
+        let mut grid = Grid::from(vec![vec![9; 300]; 300]);
+        assert_eq!(grid.step(), 300 * 300);
+
+        let mut cells = vec![vec![8; 300]; 300];
+        cells[150][150] = 9;
+        let mut grid = Grid::from(cells);
+        assert_eq!(grid.step(), 300 * 300);
+    }
 }
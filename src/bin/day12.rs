@@ -1,6 +1,6 @@
-use advent_of_code_2021::Input;
+use advent_of_code_2021::{dijkstra, Answer, Input, Solution};
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::{error, fmt};
 use thiserror::Error;
@@ -8,11 +8,11 @@ use thiserror::Error;
 /// Input parse error
 #[derive(Debug, Error)]
 #[error("Input parse error")]
-struct ParseError;
+pub(crate) struct ParseError;
 
 /// A cave's name
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
-enum CaveName {
+pub(crate) enum CaveName {
     Start,
     Big(String),
     Small(String),
@@ -46,7 +46,7 @@ impl fmt::Display for CaveName {
 
 /// A system of interconnected caves
 #[derive(Debug)]
-struct Caves {
+pub(crate) struct Caves {
     paths: HashMap<CaveName, Vec<CaveName>>,
 }
 
@@ -71,10 +71,103 @@ impl<S: AsRef<str>> TryFrom<&[S]> for Caves {
 
 impl Caves {
     /// Iterator over possible paths
+    #[allow(dead_code)]
     fn paths(&self) -> PathFinder<'_> {
         PathFinder::new(self)
     }
 
+    /// Count possible paths without building them
+    pub(crate) fn count_paths(&self, extra: bool) -> usize {
+        self.count_paths_from(&CaveName::Start, &mut HashSet::new(), !extra)
+    }
+
+    /// Count possible paths without building them, searching paths through
+    /// each exit of the start cave in parallel
+    #[cfg(feature = "parallel")]
+    #[allow(dead_code)]
+    fn count_paths_parallel(&self, extra: bool) -> usize {
+        use rayon::prelude::*;
+
+        let exits: Vec<_> = self.possible_exits_for(&CaveName::Start).collect();
+        exits
+            .into_par_iter()
+            .map(|exit| self.count_paths_via(exit, &mut HashSet::new(), !extra))
+            .sum()
+    }
+
+    /// Count possible paths from the given cave to the end, given the set of
+    /// small caves already visited and whether a small cave was visited twice
+    fn count_paths_from<'a>(
+        &'a self,
+        name: &'a CaveName,
+        visited: &mut HashSet<&'a CaveName>,
+        dupe: bool,
+    ) -> usize {
+        self.possible_exits_for(name)
+            .map(|exit| self.count_paths_via(exit, visited, dupe))
+            .sum()
+    }
+
+    /// Count possible paths to the end that continue with the given exit
+    fn count_paths_via<'a>(
+        &'a self,
+        exit: &'a CaveName,
+        visited: &mut HashSet<&'a CaveName>,
+        dupe: bool,
+    ) -> usize {
+        match exit {
+            CaveName::Start => 0,
+            CaveName::End => 1,
+            CaveName::Big(_) => self.count_paths_from(exit, visited, dupe),
+            CaveName::Small(_) if visited.contains(exit) => {
+                if dupe {
+                    0
+                } else {
+                    self.count_paths_from(exit, visited, true)
+                }
+            }
+            CaveName::Small(_) => {
+                visited.insert(exit);
+                let count = self.count_paths_from(exit, visited, dupe);
+                visited.remove(exit);
+                count
+            }
+        }
+    }
+
+    /// Find the shortest path between the given caves (regardless of cave size)
+    #[allow(dead_code)]
+    fn shortest_path(&self, from: &CaveName, to: &CaveName) -> Option<Vec<CaveName>> {
+        dijkstra(
+            from.clone(),
+            |name| {
+                self.possible_exits_for(name)
+                    .map(|exit| (exit.clone(), 1))
+                    .collect::<Vec<_>>()
+            },
+            |name| name == to,
+        )
+        .map(|(path, _len)| path)
+    }
+
+    /// Check whether all caves are connected with each other
+    #[allow(dead_code)]
+    fn is_connected(&self) -> bool {
+        let Some(first) = self.paths.keys().next() else {
+            return true;
+        };
+        let mut visited = HashSet::from([first]);
+        let mut stack = vec![first];
+        while let Some(name) = stack.pop() {
+            for exit in self.possible_exits_for(name) {
+                if visited.insert(exit) {
+                    stack.push(exit);
+                }
+            }
+        }
+        visited.len() == self.paths.len()
+    }
+
     /// Get possible exits of given cave
     fn possible_exits_for(&self, name: &CaveName) -> impl Iterator<Item = &CaveName> {
         self.paths
@@ -84,7 +177,20 @@ impl Caves {
     }
 }
 
+/// Rule for how often small caves may be visited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+enum VisitPolicy {
+    /// Small caves may be visited once
+    Standard,
+    /// One small cave may be visited twice, others once (extra rule of part 2)
+    OneDouble,
+    /// Every small cave may be visited up to the given number of times
+    Limit(usize),
+}
+
 /// Cave path finder (iterator over possible paths)
+#[allow(dead_code)]
 struct PathFinder<'a> {
     /// Set of interconnected caves
     caves: &'a Caves,
@@ -92,12 +198,13 @@ struct PathFinder<'a> {
     path: Vec<CaveName>,
     /// Iterators of possible exits for every cave in current path
     exits: Vec<Box<dyn Iterator<Item = &'a CaveName> + 'a>>,
-    /// Enable extra rule of part 2 (allow 1 small cave)
-    extra: bool,
+    /// Rule for visiting small caves
+    policy: VisitPolicy,
     /// Name of duplicate small cave in path
     dupe: Option<CaveName>,
 }
 
+#[allow(dead_code)]
 impl<'a> PathFinder<'a> {
     /// Create new path finder for given caves
     fn new(caves: &'a Caves) -> Self {
@@ -105,7 +212,7 @@ impl<'a> PathFinder<'a> {
             caves,
             path: Vec::new(),
             exits: Vec::new(),
-            extra: false,
+            policy: VisitPolicy::Standard,
             dupe: None,
         };
         pathfinder.push(CaveName::Start);
@@ -113,8 +220,13 @@ impl<'a> PathFinder<'a> {
     }
 
     /// Enable extra rule of part 2 (allow 1 small cave)
-    fn extra(mut self) -> Self {
-        self.extra = true;
+    fn extra(self) -> Self {
+        self.with_policy(VisitPolicy::OneDouble)
+    }
+
+    /// Use the given rule for visiting small caves
+    fn with_policy(mut self, policy: VisitPolicy) -> Self {
+        self.policy = policy;
         self
     }
 
@@ -142,16 +254,26 @@ impl<'a> PathFinder<'a> {
         while !self.path.is_empty() {
             if let Some(last_cave_exits) = self.exits.last_mut() {
                 for last_cave_next_exit in last_cave_exits {
-                    let mut dupe = self.path.contains(last_cave_next_exit);
-                    if dupe
-                        && self.extra
-                        && matches!(last_cave_next_exit, CaveName::Small(_))
-                        && self.dupe.is_none()
-                    {
-                        self.dupe = Some(last_cave_next_exit.clone());
-                        dupe = false;
-                    }
-                    if !dupe || matches!(last_cave_next_exit, CaveName::Big(_)) {
+                    let allowed = match last_cave_next_exit {
+                        CaveName::Big(_) => true,
+                        CaveName::Small(_) => {
+                            let visits = self
+                                .path
+                                .iter()
+                                .filter(|name| *name == last_cave_next_exit)
+                                .count();
+                            match self.policy {
+                                VisitPolicy::OneDouble if visits == 1 && self.dupe.is_none() => {
+                                    self.dupe = Some(last_cave_next_exit.clone());
+                                    true
+                                }
+                                VisitPolicy::Standard | VisitPolicy::OneDouble => visits == 0,
+                                VisitPolicy::Limit(limit) => visits < limit,
+                            }
+                        }
+                        CaveName::Start | CaveName::End => !self.path.contains(last_cave_next_exit),
+                    };
+                    if allowed {
                         self.push(last_cave_next_exit.clone());
                         return Some(last_cave_next_exit.clone());
                     }
@@ -176,15 +298,35 @@ impl<'a> Iterator for PathFinder<'a> {
     }
 }
 
+/// Day 12 solution
+#[allow(dead_code)]
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: usize = 12;
+
+    fn part1(input: Input) -> Answer {
+        let lines: Vec<_> = input.lines().try_collect()?;
+        let caves = Caves::try_from(&lines[..])?;
+        Ok(caves.count_paths(false).to_string())
+    }
+
+    fn part2(input: Input) -> Answer {
+        let lines: Vec<_> = input.lines().try_collect()?;
+        let caves = Caves::try_from(&lines[..])?;
+        Ok(caves.count_paths(true).to_string())
+    }
+}
+
 fn main() -> Result<(), Box<dyn error::Error>> {
     let lines: Vec<_> = Input::day(12)?.lines().try_collect()?;
     let caves = Caves::try_from(&lines[..])?;
 
-    println!("Number of possible paths: {}", caves.paths().count());
+    println!("Number of possible paths: {}", caves.count_paths(false));
 
     println!(
         "Number of possible paths with extra rule: {}",
-        caves.paths().extra().count()
+        caves.count_paths(true)
     );
 
     Ok(())
@@ -284,4 +426,72 @@ mod tests {
         let caves = caves3();
         assert_eq!(caves.paths().extra().count(), 3509);
     }
+
+    #[test]
+    fn visit_policy() {
+        let caves = caves3();
+        assert_eq!(
+            caves.paths().with_policy(VisitPolicy::Standard).count(),
+            226
+        );
+        assert_eq!(
+            caves.paths().with_policy(VisitPolicy::Limit(1)).count(),
+            226
+        );
+        let caves = caves2();
+        assert_eq!(
+            caves.paths().with_policy(VisitPolicy::OneDouble).count(),
+            103
+        );
+        assert_eq!(
+            caves.paths().with_policy(VisitPolicy::Limit(2)).count(),
+            247
+        );
+        let caves = caves1();
+        assert_eq!(caves.paths().with_policy(VisitPolicy::Limit(2)).count(), 54);
+    }
+
+    #[test]
+    fn shortest_path() {
+        let path = caves1().shortest_path(&CaveName::Start, &CaveName::End);
+        assert_eq!(display_path(&path.unwrap()), "start,A,end");
+        let path = caves2().shortest_path(&CaveName::Start, &CaveName::End);
+        assert_eq!(path.unwrap().len(), 3);
+        let path = caves3().shortest_path(&CaveName::Start, &CaveName::End);
+        assert_eq!(display_path(&path.unwrap()), "start,DX,fs,end");
+        let path = caves3().shortest_path(&CaveName::End, &CaveName::End);
+        assert_eq!(path.unwrap(), [CaveName::End]);
+
+        let caves = Caves::try_from(&["start-a", "b-end"][..]).unwrap();
+        assert_eq!(caves.shortest_path(&CaveName::Start, &CaveName::End), None);
+    }
+
+    #[test]
+    fn connected() {
+        assert!(caves1().is_connected());
+        assert!(caves2().is_connected());
+        assert!(caves3().is_connected());
+        let caves = Caves::try_from(&["start-a", "b-end"][..]).unwrap();
+        assert!(!caves.is_connected());
+    }
+
+    #[test]
+    fn count_paths() {
+        for caves in [caves1(), caves2(), caves3()] {
+            assert_eq!(caves.count_paths(false), caves.paths().count());
+            assert_eq!(caves.count_paths(true), caves.paths().extra().count());
+        }
+        assert_eq!(caves1().count_paths(true), 36);
+        assert_eq!(caves3().count_paths(false), 226);
+        assert_eq!(caves3().count_paths(true), 3509);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn count_paths_parallel() {
+        for caves in [caves1(), caves2(), caves3()] {
+            assert_eq!(caves.count_paths_parallel(false), caves.count_paths(false));
+            assert_eq!(caves.count_paths_parallel(true), caves.count_paths(true));
+        }
+    }
 }
@@ -32,8 +32,28 @@ impl FromStr for Fold {
     }
 }
 
+/// Font of letters (4x6 dots) that folded papers show
+const LETTERS: [(char, [&str; 6]); 16] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
 /// Piece of transparent paper
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Paper {
     dots: HashSet<(usize, usize)>,
 }
@@ -82,19 +102,76 @@ impl Paper {
             .fold((0, 0), |(w, h), (x, y)| (w.max(*x + 1), h.max(*y + 1)))
     }
 
-    /// Fold paper
+    /// Render dots as a portable bitmap (plain PBM format)
+    #[allow(dead_code)]
+    fn to_pbm(&self) -> String {
+        let (width, height) = self.dimension();
+        let mut pbm = format!("P1\n{} {}\n", width, height);
+        for y in 0..height {
+            let row = (0..width).map(|x| {
+                if self.dots.contains(&(x, y)) {
+                    '1'
+                } else {
+                    '0'
+                }
+            });
+            pbm.push_str(&row.format(" ").to_string());
+            pbm.push('\n');
+        }
+        pbm
+    }
+
+    /// Recognize letters shown by dots (4x6 dots each, separated by one
+    /// column of space)
+    fn decode_letters(&self) -> Option<String> {
+        let (width, height) = self.dimension();
+        if width == 0 || height > 6 {
+            return None;
+        }
+        (0..(width + 1).div_ceil(5))
+            .map(|i| {
+                let glyph: Vec<String> = (0..6)
+                    .map(|y| {
+                        (i * 5..i * 5 + 4)
+                            .map(|x| {
+                                if self.dots.contains(&(x, y)) {
+                                    '#'
+                                } else {
+                                    '.'
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect();
+                LETTERS
+                    .iter()
+                    .find(|(_, letter)| glyph == letter)
+                    .map(|(ch, _)| *ch)
+            })
+            .collect()
+    }
+
+    /// Fold paper. If the fold line isn't centered, dots that would be
+    /// folded beyond the edge of the paper are dropped
     fn fold(&mut self, fold: &Fold) {
         self.dots = self
             .dots
             .drain()
-            .map(|coord| match fold {
-                Fold::Horizontal(y) if coord.1 > *y => (coord.0, y - (coord.1 - y)),
-                Fold::Vertical(x) if coord.0 > *x => (x - (coord.0 - x), coord.1),
-                _ => coord,
+            .filter_map(|coord| match fold {
+                Fold::Horizontal(y) if coord.1 > *y => Some((coord.0, y.checked_sub(coord.1 - y)?)),
+                Fold::Vertical(x) if coord.0 > *x => Some((x.checked_sub(coord.0 - x)?, coord.1)),
+                _ => Some(coord),
             })
             .collect();
     }
 
+    /// Width and height of paper after folding it many times
+    fn dimension_after(&self, folds: &[Fold]) -> (usize, usize) {
+        let mut paper = self.clone();
+        paper.fold_many(folds);
+        paper.dimension()
+    }
+
     /// Fold paper many times
     fn fold_many(&mut self, folds: &[Fold]) {
         for fold in folds {
@@ -110,11 +187,17 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let lines = blocks.next().ok_or(ParseError)??;
     let folds: Vec<Fold> = lines.iter().map(|line| line.parse()).try_collect()?;
 
+    let (width, height) = paper.dimension_after(&folds);
+    println!("Size of folded paper: {}x{}", width, height);
+
     paper.fold(&folds[0]);
     println!("Number of dots after 1st fold: {}", paper.count());
 
     paper.fold_many(&folds[1..]);
     println!("Resulting folded paper:\n{}", paper);
+    if let Some(letters) = paper.decode_letters() {
+        println!("Letters: {}", letters);
+    }
 
     Ok(())
 }
@@ -146,4 +229,71 @@ mod tests {
         paper.fold(&folds[1]);
         assert_eq!(paper.count(), 16);
     }
+
+    #[test]
+    fn pbm() {
+        let (mut paper, folds) = (paper(), folds());
+        paper.fold_many(&folds);
+        let pbm = paper.to_pbm();
+        let mut lines = pbm.lines();
+        assert_eq!(lines.next(), Some("P1"));
+        assert_eq!(lines.next(), Some("5 5"));
+        assert_eq!(lines.next(), Some("1 1 1 1 1"));
+        assert_eq!(lines.next(), Some("1 0 0 0 1"));
+        assert_eq!(lines.nth(2), Some("1 1 1 1 1"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn dimension_after() {
+        let (paper, folds) = (paper(), folds());
+        assert_eq!(paper.dimension(), (11, 15));
+        assert_eq!(paper.dimension_after(&folds[..1]), (11, 5));
+        assert_eq!(paper.dimension_after(&folds), (5, 5));
+        assert_eq!(paper.count(), 18);
+    }
+
+    #[test]
+    fn off_center_fold() {
+        let paper = Paper::try_from(&["0,0", "1,5", "2,10", "4,1"][..]).unwrap();
+        let mut folded = paper.clone();
+        folded.fold(&Fold::Horizontal(3));
+        assert_eq!(folded.dots, HashSet::from([(0, 0), (1, 1), (4, 1)]));
+        assert_eq!(paper.dimension_after(&[Fold::Horizontal(3)]), (5, 2));
+
+        folded.fold(&Fold::Vertical(2));
+        assert_eq!(folded.dots, HashSet::from([(0, 0), (1, 1), (0, 1)]));
+        folded.fold(&Fold::Vertical(0));
+        assert_eq!(folded.dots, HashSet::from([(0, 0), (0, 1)]));
+        assert_eq!(
+            paper.dimension_after(&[Fold::Horizontal(3), Fold::Vertical(2)]),
+            (2, 2)
+        );
+    }
+
+    fn paper_from_picture(picture: &[&str]) -> Paper {
+        let dots = picture
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| line.match_indices('#').map(move |(x, _)| (x, y)))
+            .collect();
+        Paper { dots }
+    }
+
+    #[test]
+    fn letters() {
+        let hello = paper_from_picture(&[
+            "#..#.####.#....#.....##.",
+            "#..#.#....#....#....#..#",
+            "####.###..#....#....#..#",
+            "#..#.#....#....#....#..#",
+            "#..#.#....#....#....#..#",
+            "#..#.####.####.####..##.",
+        ]);
+        assert_eq!(hello.decode_letters().as_deref(), Some("HELLO"));
+
+        let (mut paper, folds) = (paper(), folds());
+        paper.fold_many(&folds);
+        assert_eq!(paper.decode_letters(), None);
+    }
 }
@@ -1,6 +1,6 @@
-use advent_of_code_2021::Input;
+use advent_of_code_2021::{Answer, Input, Solution};
 use itertools::{Itertools, MinMaxResult};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::error;
 use std::str::FromStr;
@@ -9,11 +9,11 @@ use thiserror::Error;
 /// Input parse error
 #[derive(Debug, Error)]
 #[error("Input parse error")]
-struct ParseError;
+pub(crate) struct ParseError;
 
 /// Polymer pair insertion rule set
 #[derive(Debug)]
-struct Rules(HashMap<(char, char), char>);
+pub(crate) struct Rules(HashMap<(char, char), char>);
 
 impl<S: AsRef<str>> TryFrom<&[S]> for Rules {
     type Error = ParseError;
@@ -25,10 +25,10 @@ impl<S: AsRef<str>> TryFrom<&[S]> for Rules {
             let mut left = left.trim().chars();
             let first = left.next().ok_or(ParseError)?;
             let second = left.next().ok_or(ParseError)?;
-            left.next().is_none().then(|| ()).ok_or(ParseError)?;
+            left.next().is_none().then_some(()).ok_or(ParseError)?;
             let mut right = right.trim().chars();
             let insert = right.next().ok_or(ParseError)?;
-            right.next().is_none().then(|| ()).ok_or(ParseError)?;
+            right.next().is_none().then_some(()).ok_or(ParseError)?;
             rules.insert((first, second), insert);
         }
         Ok(Self(rules))
@@ -40,6 +40,45 @@ impl Rules {
     fn get(&self, a: char, b: char) -> Option<char> {
         self.0.get(&(a, b)).copied()
     }
+
+    /// All element groups that can ever occur when evolving the given polymer
+    fn reachable_pairs(&self, polymer: &Polymer) -> HashSet<(char, char)> {
+        let mut reachable: HashSet<_> = polymer.groups.keys().copied().collect();
+        let mut queue: Vec<_> = reachable.iter().copied().collect();
+        while let Some((a, b)) = queue.pop() {
+            if let Some(insert) = self.get(a, b) {
+                for pair in [(a, insert), (insert, b)] {
+                    if reachable.insert(pair) {
+                        queue.push(pair);
+                    }
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Element groups that can occur when evolving the given polymer but
+    /// have no insertion rule
+    #[allow(dead_code)]
+    fn missing_pairs(&self, polymer: &Polymer) -> Vec<(char, char)> {
+        self.reachable_pairs(polymer)
+            .into_iter()
+            .filter(|pair| !self.0.contains_key(pair))
+            .sorted()
+            .collect()
+    }
+
+    /// Insertion rules that never apply when evolving the given polymer
+    #[allow(dead_code)]
+    fn unused(&self, polymer: &Polymer) -> Vec<(char, char)> {
+        let reachable = self.reachable_pairs(polymer);
+        self.0
+            .keys()
+            .filter(|pair| !reachable.contains(pair))
+            .copied()
+            .sorted()
+            .collect()
+    }
 }
 
 /// Polymer
@@ -47,9 +86,12 @@ impl Rules {
 /// For performance and scaling reasons, this doesn't keep the whole polymer
 /// string but only a count of unique element groups
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Polymer {
+pub(crate) struct Polymer {
     groups: HashMap<(char, char), usize>,
     last: (char, char),
+    /// Initial polymer string and number of steps applied to it since
+    template: String,
+    steps: usize,
 }
 
 impl FromStr for Polymer {
@@ -64,13 +106,21 @@ impl FromStr for Polymer {
                 groups
             });
         let last = s.chars().tuple_windows().last().ok_or(ParseError)?;
-        Ok(Self { groups, last })
+        Ok(Self {
+            groups,
+            last,
+            template: s.into(),
+            steps: 0,
+        })
     }
 }
 
 impl Polymer {
+    /// Maximum length of polymer string to reconstruct
+    const MAX_STRING_LEN: usize = 1 << 20;
+
     /// Calculate actual length of polymer
-    #[cfg(test)]
+    #[allow(dead_code)]
     fn len(&self) -> usize {
         self.groups.values().sum::<usize>() + 1
     }
@@ -91,15 +141,48 @@ impl Polymer {
         if let Some(insert) = rules.get(self.last.0, self.last.1) {
             self.last.0 = insert;
         }
+        self.steps += 1;
     }
 
     /// Apply multiple steps using the given rules
-    fn process(&mut self, steps: usize, rules: &Rules) {
+    pub(crate) fn process(&mut self, steps: usize, rules: &Rules) {
         for _ in 0..steps {
             self.step(rules);
         }
     }
 
+    /// Reconstruct actual polymer string after the given number of steps
+    /// using the given rules by naively expanding the initial polymer string.
+    /// Returns `None` if the string may get too long
+    #[allow(dead_code)]
+    fn to_string_after(&self, steps: usize, rules: &Rules) -> Option<String> {
+        // Every step at most doubles the number of element groups
+        let steps = self.steps.checked_add(steps)?;
+        let max_len = u32::try_from(steps).ok().and_then(|steps| {
+            self.template
+                .chars()
+                .count()
+                .saturating_sub(1)
+                .checked_mul(2usize.checked_pow(steps)?)
+        })?;
+        if max_len >= Self::MAX_STRING_LEN {
+            return None;
+        }
+        let mut polymer = self.template.clone();
+        for _ in 0..steps {
+            let mut next = String::with_capacity(polymer.len() * 2);
+            for (a, b) in polymer.chars().tuple_windows() {
+                next.push(a);
+                if let Some(insert) = rules.get(a, b) {
+                    next.push(insert);
+                }
+            }
+            next.extend(polymer.chars().last());
+            polymer = next;
+        }
+        Some(polymer)
+    }
+
     /// Counts of polymer elements
     fn counts(&self) -> HashMap<char, usize> {
         self.groups
@@ -110,6 +193,15 @@ impl Polymer {
             })
     }
 
+    /// Counts of polymer elements after the given number of steps using the
+    /// given rules
+    #[allow(dead_code)]
+    fn counts_after(&self, steps: usize, rules: &Rules) -> HashMap<char, usize> {
+        let mut polymer = self.clone();
+        polymer.process(steps, rules);
+        polymer.counts()
+    }
+
     /// Calculate most-least-score
     fn most_least_score(&self) -> usize {
         match self.counts().values().minmax() {
@@ -118,25 +210,53 @@ impl Polymer {
             MinMaxResult::MinMax(min, max) => *max - *min,
         }
     }
+
+    /// Calculate most-least-score after the given number of steps using the
+    /// given rules
+    fn score_after(&self, steps: usize, rules: &Rules) -> usize {
+        let mut polymer = self.clone();
+        polymer.process(steps, rules);
+        polymer.most_least_score()
+    }
 }
 
-fn main() -> Result<(), Box<dyn error::Error>> {
-    let mut input = Input::day(14)?;
-    let mut polymer: Polymer = input.line()?.parse()?;
-    input.line()?;
-    let lines: Vec<_> = input.lines().try_collect()?;
+/// Parse polymer template and insertion rules
+pub(crate) fn parse_input(mut input: Input) -> Result<(Polymer, Rules), Box<dyn error::Error>> {
+    let polymer = input.line()?.parse()?;
+    let lines = input.rest_blocks().next().ok_or("Missing rules")??;
     let rules = Rules::try_from(&lines[..])?;
+    Ok((polymer, rules))
+}
+
+/// Day 14 solution
+#[allow(dead_code)]
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: usize = 14;
+
+    fn part1(input: Input) -> Answer {
+        let (polymer, rules) = parse_input(input)?;
+        Ok(polymer.score_after(10, &rules).to_string())
+    }
+
+    fn part2(input: Input) -> Answer {
+        let (polymer, rules) = parse_input(input)?;
+        Ok(polymer.score_after(40, &rules).to_string())
+    }
+}
+
+fn main() -> Result<(), Box<dyn error::Error>> {
+    let (polymer, rules) = parse_input(Input::day(14)?)?;
 
-    polymer.process(10, &rules);
     println!(
         "Most/least common element score (10 steps): {}",
-        polymer.most_least_score()
+        polymer.score_after(10, &rules)
     );
 
-    polymer.process(30, &rules);
     println!(
         "Most/least common element score (40 steps): {}",
-        polymer.most_least_score()
+        polymer.score_after(40, &rules)
     );
 
     Ok(())
@@ -203,6 +323,63 @@ mod tests {
         assert_eq!(polymer.most_least_score(), 1588);
     }
 
+    #[test]
+    fn score_after() {
+        let rules = rules();
+        let polymer = polymer();
+        assert_eq!(polymer.score_after(10, &rules), 1588);
+        assert_eq!(polymer.score_after(40, &rules), 2188189693529);
+        assert_eq!(polymer.counts_after(10, &rules)[&'B'], 1749);
+        assert_eq!(polymer.counts_after(10, &rules)[&'H'], 161);
+        assert_eq!(polymer, self::polymer());
+    }
+
+    #[test]
+    fn rule_coverage() {
+        let rules = rules();
+        let polymer = polymer();
+        assert_eq!(rules.missing_pairs(&polymer), []);
+        assert_eq!(rules.unused(&polymer), []);
+
+        let incomplete: Vec<_> = RULES
+            .iter()
+            .filter(|rule| !rule.starts_with("NN") && !rule.starts_with("BB"))
+            .chain(["XY -> Z"].iter())
+            .collect();
+        let incomplete = Rules::try_from(&incomplete[..]).unwrap();
+        assert_eq!(incomplete.missing_pairs(&polymer), [('B', 'B'), ('N', 'N')]);
+        assert_eq!(incomplete.unused(&polymer), [('B', 'N'), ('X', 'Y')]);
+    }
+
+    #[test]
+    fn reconstruct() {
+        let rules = rules();
+        let polymer = polymer();
+        assert_eq!(polymer.to_string_after(0, &rules).unwrap(), "NNCB");
+        assert_eq!(polymer.to_string_after(1, &rules).unwrap(), "NCNBCHB");
+        let s = polymer.to_string_after(4, &rules).unwrap();
+        assert_eq!(s, "NBBNBNBBCCNBCNCCNBBNBBNBBBNBBNBBCBHCBHHNHCBBCBHCB");
+
+        let mut processed = polymer.clone();
+        processed.process(4, &rules);
+        assert_eq!(processed.to_string_after(0, &rules).unwrap(), s);
+        let counts = s.chars().fold(HashMap::new(), |mut counts, ch| {
+            *counts.entry(ch).or_insert(0) += 1;
+            counts
+        });
+        assert_eq!(counts, processed.counts());
+
+        let s = polymer.to_string_after(10, &rules).unwrap();
+        processed.process(6, &rules);
+        assert_eq!(s.len(), processed.len());
+
+        assert_eq!(polymer.to_string_after(40, &rules), None);
+        assert_eq!(processed.to_string_after(14, &rules), None);
+        processed.process(10, &rules);
+        assert_eq!(processed.to_string_after(0, &rules), None);
+        assert_eq!(processed.to_string_after(usize::MAX, &rules), None);
+    }
+
     #[test]
     fn part_2() {
         let rules = rules();
@@ -1,4 +1,4 @@
-use advent_of_code_2021::Input;
+use advent_of_code_2021::{astar, Answer, Input, Solution};
 use itertools::Itertools;
 use std::convert::TryFrom;
 use std::error;
@@ -7,123 +7,190 @@ use thiserror::Error;
 /// Input parse error
 #[derive(Debug, Error)]
 #[error("Input parse error")]
-struct ParseError;
+pub(crate) struct ParseError;
 
 /// Map with risk levels of the ceiling
+///
+/// The map may be virtually enlarged by a factor, in which case risk levels
+/// of the tiled map are calculated on the fly
 #[derive(Debug)]
-struct Map(Vec<Vec<u8>>);
+pub(crate) struct Map {
+    risks: Vec<Vec<u8>>,
+    factor: usize,
+}
+
+impl From<Vec<Vec<u8>>> for Map {
+    fn from(risks: Vec<Vec<u8>>) -> Self {
+        Self { risks, factor: 1 }
+    }
+}
 
 impl<S: AsRef<str>> TryFrom<&[S]> for Map {
     type Error = ParseError;
 
     fn try_from(lines: &[S]) -> Result<Self, Self::Error> {
-        Ok(Self(
-            lines
-                .iter()
-                .map(|line| {
-                    line.as_ref()
-                        .chars()
-                        .map(|ch| {
-                            ch.to_digit(10)
-                                .ok_or(ParseError)
-                                .and_then(|n| u8::try_from(n).map_err(|_| ParseError))
-                        })
-                        .try_collect()
-                })
-                .try_collect()?,
-        ))
+        let risks: Vec<Vec<u8>> = lines
+            .iter()
+            .map(|line| {
+                line.as_ref()
+                    .chars()
+                    .map(|ch| {
+                        ch.to_digit(10)
+                            .ok_or(ParseError)
+                            .and_then(|n| u8::try_from(n).map_err(|_| ParseError))
+                    })
+                    .try_collect()
+            })
+            .try_collect()?;
+        Ok(Self::from(risks))
     }
 }
 
 impl Map {
+    /// Virtually enlarge map by a given factor in both directions without
+    /// materializing the enlarged map
+    fn with_factor(mut self, factor: usize) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    /// Effective width and height of the (virtually enlarged) map
+    fn size(&self) -> (usize, usize) {
+        let height = self.risks.len();
+        let width = self.risks[height - 1].len();
+        (width * self.factor, height * self.factor)
+    }
+
+    /// Risk level at the given position of the (virtually enlarged) map.
+    /// Risk levels above 9 wrap back around to 1, a risk level of 0 stays 0
+    /// on the original tile
+    fn risk_at(&self, x: usize, y: usize) -> u8 {
+        let height = self.risks.len();
+        let width = self.risks[height - 1].len();
+        let increment = (y / height + x / width) % 9;
+        let risk = usize::from(self.risks[y % height][x % width]) + increment;
+        risk.checked_sub(1).map_or(0, |risk| (risk % 9 + 1) as u8)
+    }
+
     /// Find path with lowest risk sum (Dijkstra algorithm)
-    fn pathfinder(&self) -> Option<usize> {
-        #[derive(Debug, Clone, Default)]
-        struct BestPath {
-            risk: Option<usize>,
-            from: Option<(usize, usize)>,
-            done: bool,
-        }
+    pub(crate) fn pathfinder(&self) -> Option<usize> {
+        self.lowest_risk(false, |_y, _x| 0)
+            .map(|(_path, risk)| risk)
+    }
 
-        let height = self.0.len();
-        let width = self.0[height - 1].len();
-        let mut bestpaths = vec![vec![BestPath::default(); width]; height];
-        bestpaths[0][0].risk = Some(0);
-
-        let next_undone_with_least_risk =
-            |bestpaths: &[Vec<BestPath>]| -> Option<(usize, usize, usize)> {
-                bestpaths
-                    .iter()
-                    .enumerate()
-                    .flat_map(|(y, row)| {
-                        row.iter()
-                            .enumerate()
-                            .map(move |(x, bestpath)| (y, x, bestpath))
-                    })
-                    .filter_map(|(y, x, bestpath)| {
-                        bestpath
-                            .risk
-                            .filter(|_risk| !bestpath.done)
-                            .map(|risk| (y, x, risk))
-                    })
-                    .min_by_key(|(_y, _x, risk)| *risk)
-            };
+    /// Find path with lowest risk sum (A* algorithm, using the manhattan
+    /// distance to the bottom-right position as heuristic). The heuristic
+    /// assumes that every step costs at least 1, so it's only used if no
+    /// position has a risk level of 0
+    #[allow(dead_code)]
+    fn pathfinder_astar(&self) -> Option<usize> {
+        let (width, height) = self.size();
+        let min_step = usize::from(self.risks.iter().flatten().all(|risk| *risk > 0));
+        self.lowest_risk(false, |y, x| {
+            min_step * ((height - 1 - y) + (width - 1 - x))
+        })
+        .map(|(_path, risk)| risk)
+    }
 
-        while let Some((y, x, risk)) = next_undone_with_least_risk(&bestpaths) {
-            bestpaths[y][x].done = true;
-            if y == height - 1 && x == width - 1 {
-                break;
-            }
-            for (neighbor_y, neighbor_x) in [
-                (y < height - 1).then(|| (y + 1, x)),
-                (x < width - 1).then(|| (y, x + 1)),
-                (y > 0).then(|| (y - 1, x)),
-                (x > 0).then(|| (y, x - 1)),
-            ]
-            .into_iter()
-            .flatten()
-            {
-                let mut neighbor_bestpath = &mut bestpaths[neighbor_y][neighbor_x];
-                if !neighbor_bestpath.done {
-                    let new_neighbor_risk = risk + self.0[neighbor_y][neighbor_x] as usize;
-                    if neighbor_bestpath.risk.is_none()
-                        || new_neighbor_risk < neighbor_bestpath.risk.unwrap()
-                    {
-                        neighbor_bestpath.risk = Some(new_neighbor_risk);
-                        neighbor_bestpath.from = Some((y, x));
-                    }
-                }
-            }
-        }
+    /// Find path with lowest risk sum if diagonal moves are allowed as well
+    /// (Dijkstra algorithm)
+    #[allow(dead_code)]
+    fn pathfinder_with_diagonals(&self) -> Option<usize> {
+        self.lowest_risk(true, |_y, _x| 0).map(|(_path, risk)| risk)
+    }
 
-        bestpaths[height - 1][width - 1].risk
+    /// Find path with lowest risk sum and return the positions it passes
+    /// from top-left to bottom-right
+    #[allow(dead_code)]
+    fn lowest_risk_path(&self) -> Option<Vec<(usize, usize)>> {
+        self.lowest_risk(false, |_y, _x| 0)
+            .map(|(path, _risk)| path)
+    }
+
+    /// Find path with lowest risk sum from top-left to bottom-right and its
+    /// risk sum, visiting positions ordered by their risk plus the given
+    /// heuristic (A* algorithm, or Dijkstra algorithm if the heuristic is
+    /// zero). The heuristic must not overestimate the remaining risk.
+    /// Optionally allows diagonal moves in addition to horizontal and
+    /// vertical ones
+    fn lowest_risk<H: Fn(usize, usize) -> usize>(
+        &self,
+        diagonals: bool,
+        heuristic: H,
+    ) -> Option<(Vec<(usize, usize)>, usize)> {
+        let (width, height) = self.size();
+        astar(
+            (0, 0),
+            |&(y, x)| {
+                self.neighbors(y, x, diagonals)
+                    .map(|(y, x)| ((y, x), self.risk_at(x, y) as usize))
+            },
+            |&(y, x)| heuristic(y, x),
+            |&pos| pos == (height - 1, width - 1),
+        )
+    }
+
+    /// Adjacent positions of the given position that are within the map,
+    /// optionally including diagonally adjacent positions
+    fn neighbors(
+        &self,
+        y: usize,
+        x: usize,
+        diagonals: bool,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = self.size();
+        let diagonal_neighbors = [
+            (y < height - 1 && x < width - 1).then(|| (y + 1, x + 1)),
+            (y > 0 && x < width - 1).then(|| (y - 1, x + 1)),
+            (y < height - 1 && x > 0).then(|| (y + 1, x - 1)),
+            (y > 0 && x > 0).then(|| (y - 1, x - 1)),
+        ];
+        [
+            (y < height - 1).then(|| (y + 1, x)),
+            (x < width - 1).then(|| (y, x + 1)),
+            (y > 0).then(|| (y - 1, x)),
+            (x > 0).then(|| (y, x - 1)),
+        ]
+        .into_iter()
+        .chain(diagonal_neighbors.into_iter().filter(move |_| diagonals))
+        .flatten()
     }
 
     /// Enlarge map by a given factor in both direction
-    fn enlarge(&mut self, factor: usize) {
-        let new_map: Vec<Vec<u8>> = (0..factor)
-            .flat_map(|yy| {
-                self.0.iter().map(move |row| {
-                    (0..factor)
-                        .flat_map(|xx| {
-                            row.iter()
-                                .map(move |risk| (risk + yy as u8 + xx as u8 - 1) % 9 + 1)
-                        })
-                        .collect()
-                })
-            })
+    #[allow(dead_code)]
+    pub(crate) fn enlarge(&mut self, factor: usize) {
+        self.factor *= factor;
+        let (width, height) = self.size();
+        self.risks = (0..height)
+            .map(|y| (0..width).map(|x| self.risk_at(x, y)).collect())
             .collect();
-        self.0 = new_map;
+        self.factor = 1;
     }
 }
 
-fn main() -> Result<(), Box<dyn error::Error>> {
-    let lines: Vec<_> = Input::day(15)?.lines().try_collect()?;
+/// Day 15 solution
+#[allow(dead_code)]
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: usize = 15;
 
-    let mut map = Map::try_from(&lines[..])?;
+    fn part1(input: Input) -> Answer {
+        let map = Map::from(input.digit_grid()?);
+        Ok(map.pathfinder().unwrap_or(0).to_string())
+    }
+
+    fn part2(input: Input) -> Answer {
+        let map = Map::from(input.digit_grid()?).with_factor(5);
+        Ok(map.pathfinder().unwrap_or(0).to_string())
+    }
+}
+
+fn main() -> Result<(), Box<dyn error::Error>> {
+    let map = Map::from(Input::day(15)?.digit_grid()?);
     println!("Lowest risk: {}", map.pathfinder().unwrap_or(0));
 
-    map.enlarge(5);
+    let map = map.with_factor(5);
     println!("Lowest risk (full map): {}", map.pathfinder().unwrap_or(0));
 
     Ok(())
@@ -150,6 +217,37 @@ mod tests {
         Map::try_from(&MAP[..]).unwrap()
     }
 
+    /// Reference implementation scanning the whole map for the next position
+    /// to visit (Dijkstra algorithm without priority queue)
+    fn pathfinder_scan(map: &Map) -> Option<usize> {
+        let (width, height) = map.size();
+        let mut risks = vec![vec![None; width]; height];
+        let mut done = vec![vec![false; width]; height];
+        risks[0][0] = Some(0);
+        while let Some((y, x, risk)) = (0..height)
+            .cartesian_product(0..width)
+            .filter(|&(y, x)| !done[y][x])
+            .filter_map(|(y, x)| risks[y][x].map(|risk: usize| (y, x, risk)))
+            .min_by_key(|(_y, _x, risk)| *risk)
+        {
+            done[y][x] = true;
+            for (ny, nx) in [
+                (y + 1, x),
+                (y, x + 1),
+                (y.wrapping_sub(1), x),
+                (y, x.wrapping_sub(1)),
+            ] {
+                if ny < height && nx < width && !done[ny][nx] {
+                    let new_risk = risk + map.risk_at(nx, ny) as usize;
+                    if risks[ny][nx].is_none_or(|r| new_risk < r) {
+                        risks[ny][nx] = Some(new_risk);
+                    }
+                }
+            }
+        }
+        risks[height - 1][width - 1]
+    }
+
     #[test]
     fn part_1() {
         let map = map();
@@ -160,15 +258,123 @@ mod tests {
     fn part_2() {
         let mut map = map();
         map.enlarge(5);
-        assert_eq!(map.0.len(), 50);
-        assert_eq!(map.0[0].len(), 50);
-        assert_eq!(map.0[0][..10], [1, 1, 6, 3, 7, 5, 1, 7, 4, 2]);
-        assert_eq!(map.0[0][10..20], [2, 2, 7, 4, 8, 6, 2, 8, 5, 3]);
-        assert_eq!(map.0[0][20..30], [3, 3, 8, 5, 9, 7, 3, 9, 6, 4]);
-        assert_eq!(map.0[0][30..40], [4, 4, 9, 6, 1, 8, 4, 1, 7, 5]);
-        assert_eq!(map.0[0][40..], [5, 5, 1, 7, 2, 9, 5, 2, 8, 6]);
-        assert_eq!(map.0[49][..10], [6, 7, 5, 5, 4, 8, 8, 9, 3, 5]);
-        assert_eq!(map.0[49][40..], [1, 2, 9, 9, 8, 3, 3, 4, 7, 9]);
+        assert_eq!(map.risks.len(), 50);
+        assert_eq!(map.risks[0].len(), 50);
+        assert_eq!(map.risks[0][..10], [1, 1, 6, 3, 7, 5, 1, 7, 4, 2]);
+        assert_eq!(map.risks[0][10..20], [2, 2, 7, 4, 8, 6, 2, 8, 5, 3]);
+        assert_eq!(map.risks[0][20..30], [3, 3, 8, 5, 9, 7, 3, 9, 6, 4]);
+        assert_eq!(map.risks[0][30..40], [4, 4, 9, 6, 1, 8, 4, 1, 7, 5]);
+        assert_eq!(map.risks[0][40..], [5, 5, 1, 7, 2, 9, 5, 2, 8, 6]);
+        assert_eq!(map.risks[49][..10], [6, 7, 5, 5, 4, 8, 8, 9, 3, 5]);
+        assert_eq!(map.risks[49][40..], [1, 2, 9, 9, 8, 3, 3, 4, 7, 9]);
         assert_eq!(map.pathfinder(), Some(315));
     }
+
+    #[test]
+    fn pathfinder_matches_scan() {
+        let mut map = map();
+        assert_eq!(map.pathfinder(), pathfinder_scan(&map));
+        assert_eq!(map.pathfinder(), map.pathfinder_astar());
+        map.enlarge(3);
+        assert_eq!(map.pathfinder(), pathfinder_scan(&map));
+        assert_eq!(map.pathfinder(), map.pathfinder_astar());
+        let map = map.with_factor(2);
+        assert_eq!(map.pathfinder(), map.pathfinder_astar());
+
+        let risks: Vec<Vec<u8>> = (0..20)
+            .map(|y| {
+                (0..30)
+                    .map(|x| ((y * 7 + x * 13 + x * y) % 9 + 1) as u8)
+                    .collect()
+            })
+            .collect();
+        let map = Map::from(risks);
+        assert_eq!(map.pathfinder(), pathfinder_scan(&map));
+    }
+
+    #[test]
+    fn lowest_risk_path() {
+        let map = map();
+        let path = map.lowest_risk_path().unwrap();
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[path.len() - 1], (9, 9));
+        assert!(path
+            .iter()
+            .tuple_windows()
+            .all(|((y1, x1), (y2, x2))| y1.abs_diff(*y2) + x1.abs_diff(*x2) == 1));
+        let risk: usize = path[1..]
+            .iter()
+            .map(|(y, x)| map.risk_at(*x, *y) as usize)
+            .sum();
+        assert_eq!(risk, 40);
+        assert_eq!(Some(risk), map.pathfinder());
+    }
+
+    #[test]
+    fn lazy_enlarge() {
+        let lazy = map().with_factor(5);
+        let mut enlarged = map();
+        enlarged.enlarge(5);
+        assert_eq!(lazy.size(), (50, 50));
+        for (y, row) in enlarged.risks.iter().enumerate() {
+            for (x, risk) in row.iter().enumerate() {
+                assert_eq!(lazy.risk_at(x, y), *risk);
+            }
+        }
+        assert_eq!(lazy.pathfinder(), Some(315));
+
+        let huge = map().with_factor(25);
+        assert_eq!(huge.size(), (250, 250));
+        assert_eq!(huge.risk_at(249, 249), 4); // 1 incremented by 24 + 24
+    }
+
+    #[test]
+    fn zero_risk() {
+        let map = Map::from(vec![vec![0, 9], vec![1, 0]]).with_factor(2);
+        assert_eq!(map.risk_at(0, 0), 0);
+        assert_eq!(map.risk_at(1, 0), 9);
+        assert_eq!(map.risk_at(2, 0), 1);
+        assert_eq!(map.risk_at(3, 0), 1);
+        assert_eq!(map.risk_at(3, 3), 2);
+        assert_eq!(map.pathfinder(), pathfinder_scan(&map));
+    }
+
+    #[test]
+    fn diagonals() {
+        let map = map();
+        let risk = map.pathfinder_with_diagonals().unwrap();
+        assert!(risk <= map.pathfinder().unwrap());
+        // 1 1 3 1 3 1 1 1 1 2 3 1 1 via (1,0) (2,1) (2,2) (1,3) (1,4) (2,5)
+        // (3,6) (4,7) (5,7) (6,8) (7,8) (8,9) (9,9)
+        assert_eq!(risk, 20);
+
+        // Diagonal shortcut through the low risk center: 1 + 1
+        let map = Map::from(vec![vec![1, 9, 9], vec![9, 1, 9], vec![9, 9, 1]]);
+        assert_eq!(map.pathfinder(), Some(20));
+        assert_eq!(map.pathfinder_with_diagonals(), Some(2));
+    }
+
+    #[test]
+    fn astar() {
+        let mut map = map();
+        assert_eq!(map.pathfinder_astar(), Some(40));
+        assert_eq!(map.pathfinder_astar(), map.pathfinder());
+        map.enlarge(5);
+        assert_eq!(map.pathfinder_astar(), Some(315));
+        assert_eq!(map.pathfinder_astar(), map.pathfinder());
+
+        // The manhattan distance overestimates the remaining risk on paths
+        // with risk level 0
+        let map = Map::from(vec![
+            vec![1, 2, 0, 1, 1],
+            vec![2, 2, 0, 0, 1],
+            vec![0, 2, 0, 2, 1],
+            vec![1, 0, 0, 1, 2],
+            vec![1, 1, 1, 1, 2],
+        ]);
+        assert_eq!(map.pathfinder(), Some(6));
+        assert_eq!(map.pathfinder_astar(), Some(6));
+        let map = map.with_factor(2);
+        assert_eq!(map.pathfinder_astar(), map.pathfinder());
+    }
 }
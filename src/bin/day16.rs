@@ -1,6 +1,6 @@
 use advent_of_code_2021::Input;
 use itertools::Itertools;
-use std::error;
+use std::{error, fmt};
 use thiserror::Error;
 
 /// Error variants
@@ -8,79 +8,128 @@ use thiserror::Error;
 enum Error {
     #[error("Out of input data")]
     OutOfData,
+    #[error("Unexpected end of transmission")]
+    UnexpectedEnd,
     #[error("Invalid hex digit `{0}`")]
     InvalidHexDigit(char),
+    #[error("Invalid binary digit `{0}`")]
+    InvalidBinaryDigit(char),
     #[error("Invalid packet type id {0}")]
     InvalidType(u64),
+    #[error("Non-zero bits after end of packet")]
+    NonZeroPadding,
+    #[error("Subpackets exceed their total length")]
+    LengthExceeded,
+    #[error("Invalid number of operands for packet type id {0}: {1}")]
+    BadOperandCount(u64, usize),
+    #[error("Arithmetic overflow during evaluation")]
+    Overflow,
+    #[error("Value {0} doesn't fit in {1} bits")]
+    Unencodable(u64, usize),
 }
 
-/// Parse bits from hexadecimal digits
-#[allow(clippy::needless_lifetimes)]
-fn hex2bits<'a>(s: &'a str) -> impl Iterator<Item = Result<bool, Error>> + 'a {
-    s.chars().flat_map(|ch| {
-        let n = ch.to_digit(16);
-        [
-            n.map(|n| n & 0b1000 > 0).ok_or(Error::InvalidHexDigit(ch)),
-            n.map(|n| n & 0b0100 > 0).ok_or(Error::InvalidHexDigit(ch)),
-            n.map(|n| n & 0b0010 > 0).ok_or(Error::InvalidHexDigit(ch)),
-            n.map(|n| n & 0b0001 > 0).ok_or(Error::InvalidHexDigit(ch)),
-        ]
-    })
+/// Bitstream that keeps track of the number of bits consumed
+#[derive(Debug)]
+struct BitReader<I> {
+    bits: I,
+    bits_consumed: usize,
 }
 
-/// Parse integer number from bitstream
-fn parse_number(
-    bits: &mut impl Iterator<Item = Result<bool, Error>>,
-    n: usize,
-) -> Result<u64, Error> {
-    let mut res = 0;
-    for _ in 0..n {
-        let bit = match bits.next().ok_or(Error::OutOfData)?? {
-            false => 0,
-            true => 1,
-        };
-        res = (res << 1) | bit
+impl<I: Iterator<Item = Result<bool, Error>>> BitReader<I> {
+    /// Create bitstream from an iterator of bits
+    fn new(bits: I) -> Self {
+        Self {
+            bits,
+            bits_consumed: 0,
+        }
+    }
+
+    /// Number of bits consumed so far
+    fn bits_consumed(&self) -> usize {
+        self.bits_consumed
+    }
+
+    /// Read single bit from bitstream
+    fn read_bit(&mut self) -> Result<bool, Error> {
+        self.next().ok_or(Error::OutOfData)?
     }
-    Ok(res)
-}
 
-/// Parse grouped integer number from bitstream
-fn parse_grouped_number(
-    bits: &mut impl Iterator<Item = Result<bool, Error>>,
-) -> Result<u64, Error> {
-    let mut res = 0;
-    loop {
-        let more = bits.next().ok_or(Error::OutOfData)??;
-        res = (res << 4) | parse_number(bits, 4)?;
-        if !more {
-            return Ok(res);
+    /// Read integer number of the given bit size from bitstream
+    fn read_bits(&mut self, n: usize) -> Result<u64, Error> {
+        let mut res = 0;
+        for _ in 0..n {
+            res = (res << 1) | u64::from(self.read_bit()?);
         }
+        Ok(res)
     }
-}
 
-/// Parse list of packets from bitstream
-fn parse_packet_list(
-    bits: &mut impl Iterator<Item = Result<bool, Error>>,
-) -> Result<Vec<Packet>, Error> {
-    if !bits.next().ok_or(Error::OutOfData)?? {
-        let len = parse_number(bits, 15)?;
-        let mut bits = bits.take(len as usize).collect_vec().into_iter();
-        let mut packets = Vec::new();
+    /// Read grouped integer number from bitstream
+    fn read_grouped_number(&mut self) -> Result<u64, Error> {
+        let mut res = 0;
         loop {
-            match Packet::parse(&mut bits) {
-                Ok(packet) => packets.push(packet),
-                Err(Error::OutOfData) => break,
-                Err(e) => return Err(e),
+            let more = self.read_bit()?;
+            res = (res << 4) | self.read_bits(4)?;
+            if !more {
+                return Ok(res);
             }
         }
-        Ok(packets)
-    } else {
-        let count = parse_number(bits, 11)?;
-        let packets = (0..count).map(|_| Packet::parse(bits)).try_collect()?;
-        Ok(packets)
+    }
+
+    /// Read list of packets from bitstream
+    fn read_packet_list(&mut self) -> Result<Vec<Packet>, Error> {
+        if !self.read_bit()? {
+            let len = self.read_bits(15)?;
+            let end = self.bits_consumed() + len as usize;
+            let mut packets = Vec::new();
+            while self.bits_consumed() < end {
+                packets.push(Packet::parse(self)?);
+            }
+            if self.bits_consumed() > end {
+                return Err(Error::LengthExceeded);
+            }
+            Ok(packets)
+        } else {
+            let count = self.read_bits(11)?;
+            let packets = (0..count).map(|_| Packet::parse(self)).try_collect()?;
+            Ok(packets)
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<bool, Error>>> Iterator for BitReader<I> {
+    type Item = Result<bool, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bit = self.bits.next()?;
+        self.bits_consumed += 1;
+        Some(bit)
     }
 }
 
+/// Parse bits from hexadecimal digits
+#[allow(clippy::needless_lifetimes)]
+fn hex2bits<'a>(s: &'a str) -> BitReader<impl Iterator<Item = Result<bool, Error>> + 'a> {
+    BitReader::new(s.chars().flat_map(|ch| {
+        let n = ch.to_digit(16);
+        [
+            n.map(|n| n & 0b1000 > 0).ok_or(Error::InvalidHexDigit(ch)),
+            n.map(|n| n & 0b0100 > 0).ok_or(Error::InvalidHexDigit(ch)),
+            n.map(|n| n & 0b0010 > 0).ok_or(Error::InvalidHexDigit(ch)),
+            n.map(|n| n & 0b0001 > 0).ok_or(Error::InvalidHexDigit(ch)),
+        ]
+    }))
+}
+
+/// Parse bits from binary digits
+#[allow(dead_code, clippy::needless_lifetimes)]
+fn bits_from_str<'a>(s: &'a str) -> BitReader<impl Iterator<Item = Result<bool, Error>> + 'a> {
+    BitReader::new(s.chars().map(|ch| match ch {
+        '0' => Ok(false),
+        '1' => Ok(true),
+        _ => Err(Error::InvalidBinaryDigit(ch)),
+    }))
+}
+
 /// Packet operators
 #[derive(Debug, PartialEq, Eq)]
 enum Operator {
@@ -96,18 +145,86 @@ enum Operator {
 
 impl Operator {
     /// Parse packet operator from bitstream
-    fn parse(bits: &mut impl Iterator<Item = Result<bool, Error>>) -> Result<Self, Error> {
-        Ok(match parse_number(bits, 3)? {
-            0 => Self::Sum(parse_packet_list(bits)?),
-            1 => Self::Product(parse_packet_list(bits)?),
-            2 => Self::Minimum(parse_packet_list(bits)?),
-            3 => Self::Maximum(parse_packet_list(bits)?),
-            4 => Self::Literal(parse_grouped_number(bits)?),
-            5 => Self::GreaterThan(parse_packet_list(bits)?),
-            6 => Self::LessThan(parse_packet_list(bits)?),
-            7 => Self::EqualTo(parse_packet_list(bits)?),
+    fn parse(
+        bits: &mut BitReader<impl Iterator<Item = Result<bool, Error>>>,
+    ) -> Result<Self, Error> {
+        let operator = match bits.read_bits(3)? {
+            0 => Self::Sum(bits.read_packet_list()?),
+            1 => Self::Product(bits.read_packet_list()?),
+            2 => Self::Minimum(bits.read_packet_list()?),
+            3 => Self::Maximum(bits.read_packet_list()?),
+            4 => Self::Literal(bits.read_grouped_number()?),
+            5 => Self::GreaterThan(bits.read_packet_list()?),
+            6 => Self::LessThan(bits.read_packet_list()?),
+            7 => Self::EqualTo(bits.read_packet_list()?),
             id => return Err(Error::InvalidType(id)),
-        })
+        };
+        let count = operator.children().len();
+        let valid = match operator {
+            Self::Literal(_) => true,
+            Self::GreaterThan(_) | Self::LessThan(_) | Self::EqualTo(_) => count == 2,
+            _ => count > 0,
+        };
+        if !valid {
+            return Err(Error::BadOperandCount(operator.type_id(), count));
+        }
+        Ok(operator)
+    }
+
+    /// Packet type id of operator
+    fn type_id(&self) -> u64 {
+        match self {
+            Self::Sum(_) => 0,
+            Self::Product(_) => 1,
+            Self::Minimum(_) => 2,
+            Self::Maximum(_) => 3,
+            Self::Literal(_) => 4,
+            Self::GreaterThan(_) => 5,
+            Self::LessThan(_) => 6,
+            Self::EqualTo(_) => 7,
+        }
+    }
+
+    /// Operand packets of operator
+    fn children(&self) -> &[Packet] {
+        match self {
+            Self::Literal(_) => &[],
+            Self::Sum(packets)
+            | Self::Product(packets)
+            | Self::Minimum(packets)
+            | Self::Maximum(packets)
+            | Self::GreaterThan(packets)
+            | Self::LessThan(packets)
+            | Self::EqualTo(packets) => packets,
+        }
+    }
+
+    /// Apply operator to the given operand values
+    fn apply(&self, mut values: impl Iterator<Item = u64>) -> u64 {
+        match self {
+            Self::Sum(_) => values.sum(),
+            Self::Product(_) => values.product(),
+            Self::Minimum(_) => values.min().unwrap(),
+            Self::Maximum(_) => values.max().unwrap(),
+            Self::Literal(value) => *value,
+            Self::GreaterThan(_) => u64::from(values.next() > values.next()),
+            Self::LessThan(_) => u64::from(values.next() < values.next()),
+            Self::EqualTo(_) => u64::from(values.next() == values.next()),
+        }
+    }
+
+    /// Name of operator
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Sum(_) => "Sum",
+            Self::Product(_) => "Product",
+            Self::Minimum(_) => "Minimum",
+            Self::Maximum(_) => "Maximum",
+            Self::Literal(_) => "Literal",
+            Self::GreaterThan(_) => "GreaterThan",
+            Self::LessThan(_) => "LessThan",
+            Self::EqualTo(_) => "EqualTo",
+        }
     }
 }
 
@@ -120,66 +237,236 @@ struct Packet {
 
 impl Packet {
     /// Parse packet from bitstream
-    fn parse(bits: &mut impl Iterator<Item = Result<bool, Error>>) -> Result<Self, Error> {
+    fn parse(
+        bits: &mut BitReader<impl Iterator<Item = Result<bool, Error>>>,
+    ) -> Result<Self, Error> {
         Ok(Self {
-            version: parse_number(bits, 3)?,
+            version: bits.read_bits(3)?,
             operator: Operator::parse(bits)?,
         })
     }
 
-    /// Sum of version numbers
-    fn version_sum(&self) -> u64 {
-        self.version
-            + self
+    /// Parse packet from bitstream and return the remaining bits
+    fn parse_with_trailing(
+        bits: &mut BitReader<impl Iterator<Item = Result<bool, Error>>>,
+    ) -> Result<(Self, Vec<bool>), Error> {
+        let packet = Self::parse(bits).map_err(|e| match e {
+            Error::OutOfData => Error::UnexpectedEnd,
+            e => e,
+        })?;
+        let trailing = bits.collect::<Result<_, _>>()?;
+        Ok((packet, trailing))
+    }
+
+    /// Parse a single packet from hexadecimal digits, making sure that only
+    /// zero bits are left for padding
+    fn parse_toplevel(hex: &str) -> Result<Self, Error> {
+        let (packet, trailing) = Self::parse_with_trailing(&mut hex2bits(hex))?;
+        if trailing.contains(&true) {
+            return Err(Error::NonZeroPadding);
+        }
+        Ok(packet)
+    }
+
+    /// Version numbers of this packet and all subpackets
+    fn versions(&self) -> Vec<u64> {
+        let mut versions = vec![self.version];
+        for packet in self.subpackets() {
+            versions.extend(packet.versions());
+        }
+        versions
+    }
+
+    /// Literal values of this packet and all subpackets
+    fn literals(&self) -> Vec<u64> {
+        match self.operator {
+            Operator::Literal(value) => vec![value],
+            _ => self
                 .subpackets()
                 .iter()
-                .map(|p| p.version_sum())
-                .sum::<u64>()
+                .flat_map(Packet::literals)
+                .collect(),
+        }
+    }
+
+    /// Sum of version numbers
+    fn version_sum(&self) -> u64 {
+        self.versions().iter().sum()
+    }
+
+    /// Number of packets, including this one
+    fn packet_count(&self) -> usize {
+        1 + self
+            .subpackets()
+            .iter()
+            .map(Packet::packet_count)
+            .sum::<usize>()
+    }
+
+    /// Maximum nesting depth, a single packet having depth 1
+    fn depth(&self) -> usize {
+        1 + self
+            .subpackets()
+            .iter()
+            .map(Packet::depth)
+            .max()
+            .unwrap_or(0)
     }
 
     /// Evaluate the packet
+    #[allow(dead_code)]
     fn eval(&self) -> u64 {
-        match self.operator {
-            Operator::Sum(ref packets) => packets.iter().map(|p| p.eval()).sum(),
-            Operator::Product(ref packets) => packets.iter().map(|p| p.eval()).product(),
-            Operator::Minimum(ref packets) => packets.iter().map(|p| p.eval()).min().unwrap(),
-            Operator::Maximum(ref packets) => packets.iter().map(|p| p.eval()).max().unwrap(),
+        self.operator
+            .apply(self.subpackets().iter().map(Packet::eval))
+    }
+
+    /// Evaluate the packet using an explicit stack instead of recursion
+    #[allow(dead_code)]
+    fn eval_iter(&self) -> u64 {
+        let mut stack = vec![(self, false)];
+        let mut values = Vec::new();
+        while let Some((packet, expanded)) = stack.pop() {
+            let subpackets = packet.subpackets();
+            if expanded || subpackets.is_empty() {
+                let operands = values.split_off(values.len() - subpackets.len());
+                values.push(packet.operator.apply(operands.into_iter()));
+            } else {
+                stack.push((packet, true));
+                stack.extend(subpackets.iter().rev().map(|p| (p, false)));
+            }
+        }
+        values[0]
+    }
+
+    /// Evaluate the packet, failing on arithmetic overflow
+    fn eval_checked(&self) -> Result<u64, Error> {
+        let values: Vec<u64> = self
+            .subpackets()
+            .iter()
+            .map(Packet::eval_checked)
+            .try_collect()?;
+        Ok(match self.operator {
+            Operator::Sum(_) => values
+                .into_iter()
+                .try_fold(0, u64::checked_add)
+                .ok_or(Error::Overflow)?,
+            Operator::Product(_) => values
+                .into_iter()
+                .try_fold(1, u64::checked_mul)
+                .ok_or(Error::Overflow)?,
+            Operator::Minimum(_) => values.into_iter().min().unwrap(),
+            Operator::Maximum(_) => values.into_iter().max().unwrap(),
             Operator::Literal(value) => value,
-            Operator::GreaterThan(ref packets) => (packets[0].eval() > packets[1].eval())
-                .then(|| 1)
-                .unwrap_or(0),
-            Operator::LessThan(ref packets) => (packets[0].eval() < packets[1].eval())
-                .then(|| 1)
-                .unwrap_or(0),
-            Operator::EqualTo(ref packets) => (packets[0].eval() == packets[1].eval())
-                .then(|| 1)
-                .unwrap_or(0),
+            Operator::GreaterThan(_) => u64::from(values[0] > values[1]),
+            Operator::LessThan(_) => u64::from(values[0] < values[1]),
+            Operator::EqualTo(_) => u64::from(values[0] == values[1]),
+        })
+    }
+
+    /// Encode packet to bits. Fails if a version, type id or subpacket count
+    /// doesn't fit into its number of bits
+    #[allow(dead_code)]
+    fn to_bits(&self) -> Result<Vec<bool>, Error> {
+        fn push(bits: &mut Vec<bool>, value: u64, n: usize) -> Result<(), Error> {
+            if value >> n != 0 {
+                return Err(Error::Unencodable(value, n));
+            }
+            bits.extend((0..n).rev().map(|i| value >> i & 1 == 1));
+            Ok(())
+        }
+
+        let mut bits = Vec::new();
+        push(&mut bits, self.version, 3)?;
+        push(&mut bits, self.operator.type_id(), 3)?;
+        match self.operator {
+            Operator::Literal(value) => {
+                let groups = (1..16).find(|n| value >> (n * 4) == 0).unwrap_or(16);
+                for group in (0..groups).rev() {
+                    bits.push(group > 0);
+                    push(&mut bits, value >> (group * 4) & 0xf, 4)?;
+                }
+            }
+            _ => {
+                let subpackets = self.subpackets();
+                let subbits: Vec<bool> = subpackets
+                    .iter()
+                    .map(Packet::to_bits)
+                    .flatten_ok()
+                    .try_collect()?;
+                if subbits.len() < 1 << 15 {
+                    bits.push(false);
+                    push(&mut bits, subbits.len() as u64, 15)?;
+                } else {
+                    bits.push(true);
+                    push(&mut bits, subpackets.len() as u64, 11)?;
+                }
+                bits.extend(subbits);
+            }
         }
+        Ok(bits)
+    }
+
+    /// Encode packet to hexadecimal digits, padding with zero bits
+    #[allow(dead_code)]
+    fn to_hex(&self) -> Result<String, Error> {
+        Ok(self
+            .to_bits()?
+            .chunks(4)
+            .map(|nibble| {
+                let n = (0..4).fold(0, |n, i| n << 1 | u32::from(nibble.get(i) == Some(&true)));
+                char::from_digit(n, 16).unwrap().to_ascii_uppercase()
+            })
+            .collect())
     }
 
     /// Subpackets
     fn subpackets(&self) -> &[Packet] {
+        self.operator.children()
+    }
+
+    /// Write packet tree with the given indentation level
+    fn fmt_tree(&self, f: &mut fmt::Formatter, level: usize) -> fmt::Result {
+        write!(
+            f,
+            "{:indent$}version {}, type {}: ",
+            "",
+            self.version,
+            self.operator.type_id(),
+            indent = level * 2
+        )?;
         match self.operator {
-            Operator::Sum(ref packets) => packets,
-            Operator::Product(ref packets) => packets,
-            Operator::Minimum(ref packets) => packets,
-            Operator::Maximum(ref packets) => packets,
-            Operator::Literal(_) => &[],
-            Operator::GreaterThan(ref packets) => packets,
-            Operator::LessThan(ref packets) => packets,
-            Operator::EqualTo(ref packets) => packets,
+            Operator::Literal(value) => writeln!(f, "Literal({value})"),
+            ref operator => {
+                writeln!(f, "{}", operator.name())?;
+                for packet in self.subpackets() {
+                    packet.fmt_tree(f, level + 1)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_tree(f, 0)
+    }
+}
+
 fn main() -> Result<(), Box<dyn error::Error>> {
     let line = Input::day(16)?.line()?;
-    let mut bits = hex2bits(&line);
-    let packet = Packet::parse(&mut bits).unwrap();
+    let packet = Packet::parse_toplevel(&line)?;
+
+    println!(
+        "Packets: {}, literals: {}, max depth: {}",
+        packet.packet_count(),
+        packet.literals().len(),
+        packet.depth()
+    );
 
     println!("Version sum: {}", packet.version_sum());
 
-    println!("Result: {}", packet.eval());
+    println!("Result: {}", packet.eval_checked()?);
 
     Ok(())
 }
@@ -200,6 +487,52 @@ mod tests {
         assert_eq!(bits2string(bits), "110100101111111000101000");
     }
 
+    #[test]
+    fn binary_bits() {
+        let bits = bits_from_str("110100101111111000101000");
+        assert_eq!(bits2string(bits), "110100101111111000101000");
+
+        let mut bits = bits_from_str("110100101111111000101000");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(packet.version, 6);
+        assert_eq!(packet.operator, Operator::Literal(2021));
+
+        let mut bits = bits_from_str("1101002");
+        assert!(matches!(
+            Packet::parse(&mut bits),
+            Err(Error::InvalidBinaryDigit('2'))
+        ));
+    }
+
+    #[test]
+    fn bits_consumed() {
+        let mut bits = hex2bits("D2FE28");
+        assert_eq!(bits.read_bits(3).unwrap(), 6);
+        assert_eq!(bits.bits_consumed(), 3);
+        assert_eq!(bits.read_bits(3).unwrap(), 4);
+        assert_eq!(bits.read_grouped_number().unwrap(), 2021);
+        assert_eq!(bits.bits_consumed(), 21);
+
+        let mut bits = hex2bits("D2FE28");
+        Packet::parse(&mut bits).unwrap();
+        assert_eq!(bits.bits_consumed(), 21);
+
+        let mut bits = hex2bits("38006F45291200");
+        Packet::parse(&mut bits).unwrap();
+        assert_eq!(bits.bits_consumed(), 49);
+
+        let mut bits = hex2bits("EE00D40C823060");
+        assert_eq!(bits.read_bits(3).unwrap(), 7);
+        assert_eq!(bits.read_bits(3).unwrap(), 3);
+        assert!(bits.read_bit().unwrap());
+        assert_eq!(bits.bits_consumed(), 7);
+        assert_eq!(bits.read_bits(11).unwrap(), 3);
+        assert_eq!(bits.bits_consumed(), 18);
+        let packets: Vec<_> = (0..3).map(|_| Packet::parse(&mut bits).unwrap()).collect();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(bits.bits_consumed(), 51);
+    }
+
     #[test]
     fn part_1a() {
         let mut bits = hex2bits("D2FE28");
@@ -208,6 +541,231 @@ mod tests {
         assert_eq!(packet.operator, Operator::Literal(2021));
     }
 
+    #[test]
+    fn roundtrip() {
+        let packet = Packet::parse(&mut hex2bits("D2FE28")).unwrap();
+        assert_eq!(
+            bits2string(packet.to_bits().unwrap().into_iter().map(Ok)),
+            "110100101111111000101"
+        );
+        assert_eq!(packet.to_hex().unwrap(), "D2FE28");
+        assert_eq!(
+            Packet::parse(&mut hex2bits(&packet.to_hex().unwrap())).unwrap(),
+            packet
+        );
+
+        let packet = Packet::parse(&mut hex2bits("38006F45291200")).unwrap();
+        // Only pads up to the next full hexadecimal digit
+        assert_eq!(packet.to_hex().unwrap(), "38006F4529120");
+        assert_eq!(
+            Packet::parse(&mut hex2bits(&packet.to_hex().unwrap())).unwrap(),
+            packet
+        );
+
+        // Subpacket count gets encoded as subpacket length
+        let packet = Packet::parse(&mut hex2bits("EE00D40C823060")).unwrap();
+        let reparsed = Packet::parse(&mut hex2bits(&packet.to_hex().unwrap())).unwrap();
+        assert_eq!(reparsed, packet);
+        assert_eq!(reparsed.to_hex().unwrap(), packet.to_hex().unwrap());
+
+        for hex in [
+            "8A004A801A8002F478",
+            "620080001611562C8802118E34",
+            "C0015000016115A2E0802F182340",
+            "A0016C880162017C3686B18A3D4780",
+            "9C0141080250320F1802104A08",
+        ] {
+            let packet = Packet::parse(&mut hex2bits(hex)).unwrap();
+            assert_eq!(
+                Packet::parse(&mut hex2bits(&packet.to_hex().unwrap())).unwrap(),
+                packet
+            );
+        }
+    }
+
+    #[test]
+    fn unencodable() {
+        let literal = |value| Packet {
+            version: 0,
+            operator: Operator::Literal(value),
+        };
+
+        // Subpackets of 26 bits each exceed the 15 bit total length, so their
+        // count gets encoded in 11 bits instead
+        let packet = Packet {
+            version: 1,
+            operator: Operator::Sum((0..2047).map(|_| literal(0xffff)).collect()),
+        };
+        assert!(packet.to_bits().unwrap()[6]);
+        assert_eq!(
+            Packet::parse(&mut hex2bits(&packet.to_hex().unwrap())).unwrap(),
+            packet
+        );
+        let packet = Packet {
+            version: 1,
+            operator: Operator::Sum((0..2048).map(|_| literal(0xffff)).collect()),
+        };
+        assert!(matches!(
+            packet.to_bits(),
+            Err(Error::Unencodable(2048, 11))
+        ));
+
+        let packet = Packet {
+            version: 8,
+            operator: Operator::Literal(1),
+        };
+        assert!(matches!(packet.to_hex(), Err(Error::Unencodable(8, 3))));
+    }
+
+    #[test]
+    fn trailing() {
+        let mut bits = hex2bits("D2FE28");
+        let (packet, trailing) = Packet::parse_with_trailing(&mut bits).unwrap();
+        assert_eq!(packet.operator, Operator::Literal(2021));
+        assert_eq!(trailing, [false; 3]);
+
+        let bits = hex2bits("D2FE28").skip(24 - trailing.len());
+        assert_eq!(bits2string(bits), "000");
+    }
+
+    #[test]
+    fn toplevel() {
+        let (_packet, trailing) = Packet::parse_with_trailing(&mut hex2bits("D2FE29")).unwrap();
+        assert_eq!(trailing, [false, false, true]);
+
+        let packet = Packet::parse_toplevel("D2FE28").unwrap();
+        assert_eq!(packet.operator, Operator::Literal(2021));
+        let packet = Packet::parse_toplevel("38006F45291200").unwrap();
+        assert_eq!(packet.operator.type_id(), 6);
+
+        assert!(matches!(
+            Packet::parse_toplevel("D2FE29"),
+            Err(Error::NonZeroPadding)
+        ));
+        assert!(matches!(
+            Packet::parse_toplevel("D2FE2F"),
+            Err(Error::NonZeroPadding)
+        ));
+        assert!(matches!(
+            Packet::parse_toplevel("D2FE2800F"),
+            Err(Error::NonZeroPadding)
+        ));
+        assert!(matches!(
+            Packet::parse_toplevel("D2FE"),
+            Err(Error::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            Packet::parse_toplevel("D2FE2G"),
+            Err(Error::InvalidHexDigit('G'))
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut bits = hex2bits("D2FE");
+        assert!(matches!(
+            Packet::parse_with_trailing(&mut bits),
+            Err(Error::UnexpectedEnd)
+        ));
+
+        let mut bits = hex2bits("EE00D40C82");
+        assert!(matches!(
+            Packet::parse_with_trailing(&mut bits),
+            Err(Error::UnexpectedEnd)
+        ));
+
+        let mut bits = hex2bits("38006F4529");
+        assert!(matches!(
+            Packet::parse_with_trailing(&mut bits),
+            Err(Error::UnexpectedEnd)
+        ));
+
+        let mut bits = hex2bits("D2FG28");
+        assert!(matches!(
+            Packet::parse_with_trailing(&mut bits),
+            Err(Error::InvalidHexDigit('G'))
+        ));
+    }
+
+    #[test]
+    fn length_exceeded() {
+        // Operator with a total subpacket length of 10 bits, containing a 11 bit literal
+        let mut bits = hex2bits("38002B450");
+        assert!(matches!(
+            Packet::parse(&mut bits),
+            Err(Error::LengthExceeded)
+        ));
+    }
+
+    #[test]
+    fn operand_count() {
+        // Less-than operator with a single literal
+        let mut bits = hex2bits("3A007450");
+        assert!(matches!(
+            Packet::parse(&mut bits),
+            Err(Error::BadOperandCount(6, 1))
+        ));
+
+        // Maximum operator without operands
+        let mut bits = hex2bits("2E000");
+        assert!(matches!(
+            Packet::parse(&mut bits),
+            Err(Error::BadOperandCount(3, 0))
+        ));
+    }
+
+    #[test]
+    fn type_ids() {
+        assert_eq!(Operator::Sum(vec![]).type_id(), 0);
+        assert_eq!(Operator::Product(vec![]).type_id(), 1);
+        assert_eq!(Operator::Minimum(vec![]).type_id(), 2);
+        assert_eq!(Operator::Maximum(vec![]).type_id(), 3);
+        assert_eq!(Operator::Literal(0).type_id(), 4);
+        assert_eq!(Operator::GreaterThan(vec![]).type_id(), 5);
+        assert_eq!(Operator::LessThan(vec![]).type_id(), 6);
+        assert_eq!(Operator::EqualTo(vec![]).type_id(), 7);
+
+        let mut bits = hex2bits("EE00D40C823060");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(packet.operator.type_id(), 3);
+        assert_eq!(packet.operator.children().len(), 3);
+        assert!(packet
+            .operator
+            .children()
+            .iter()
+            .all(|p| p.operator.type_id() == 4 && p.operator.children().is_empty()));
+    }
+
+    #[test]
+    fn tree() {
+        let mut bits = hex2bits("D2FE28");
+        let packet = Packet::parse(&mut bits).unwrap();
+        let tree = packet.to_string();
+        assert!(tree.contains("Literal(2021)"));
+        assert!(tree.contains("version 6"));
+
+        let mut bits = hex2bits("38006F45291200");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(
+            packet.to_string(),
+            "version 1, type 6: LessThan\n  version 6, type 4: Literal(10)\n  version 2, type 4: Literal(20)\n"
+        );
+
+        let mut bits = hex2bits("EE00D40C823060");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(
+            packet.to_string(),
+            [
+                "version 7, type 3: Maximum",
+                "  version 2, type 4: Literal(1)",
+                "  version 4, type 4: Literal(2)",
+                "  version 1, type 4: Literal(3)",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
     #[test]
     fn part_1b() {
         let mut bits = hex2bits("38006F45291200");
@@ -263,6 +821,40 @@ mod tests {
         assert_eq!(packet.version_sum(), 31);
     }
 
+    #[test]
+    fn metrics() {
+        let mut bits = hex2bits("D2FE28");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(packet.packet_count(), 1);
+        assert_eq!(packet.depth(), 1);
+
+        let mut bits = hex2bits("8A004A801A8002F478");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(packet.packet_count(), 4);
+        assert_eq!(packet.depth(), 4);
+
+        let mut bits = hex2bits("A0016C880162017C3686B18A3D4780");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(packet.packet_count(), 8);
+        assert_eq!(packet.depth(), 4);
+        assert_eq!(packet.subpackets()[0].subpackets()[0].packet_count(), 6);
+        assert_eq!(packet.subpackets()[0].subpackets()[0].depth(), 2);
+    }
+
+    #[test]
+    fn versions_literals() {
+        let mut bits = hex2bits("8A004A801A8002F478");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(packet.versions(), [4, 1, 5, 6]);
+        assert_eq!(packet.versions().iter().sum::<u64>(), packet.version_sum());
+        assert_eq!(packet.literals(), [15]);
+
+        let mut bits = hex2bits("EE00D40C823060");
+        let packet = Packet::parse(&mut bits).unwrap();
+        assert_eq!(packet.versions(), [7, 2, 4, 1]);
+        assert_eq!(packet.literals(), [1, 2, 3]);
+    }
+
     #[test]
     fn part_2() {
         let mut bits = hex2bits("C200B40A82");
@@ -297,4 +889,64 @@ mod tests {
         let packet = Packet::parse(&mut bits).unwrap();
         assert_eq!(packet.eval(), 1);
     }
+
+    #[test]
+    fn part_2_iter() {
+        for (hex, value) in [
+            ("C200B40A82", 3),
+            ("04005AC33890", 54),
+            ("880086C3E88112", 7),
+            ("CE00C43D881120", 9),
+            ("D8005AC2A8F0", 1),
+            ("F600BC2D8F", 0),
+            ("9C005AC2F8F0", 0),
+            ("9C0141080250320F1802104A08", 1),
+        ] {
+            let mut bits = hex2bits(hex);
+            let packet = Packet::parse(&mut bits).unwrap();
+            assert_eq!(packet.eval_iter(), value);
+            assert_eq!(packet.eval_iter(), packet.eval());
+        }
+    }
+
+    #[test]
+    fn deeply_nested() {
+        let mut packet = Packet {
+            version: 0,
+            operator: Operator::Literal(42),
+        };
+        for _ in 0..2000 {
+            packet = Packet {
+                version: 0,
+                operator: Operator::Maximum(vec![packet]),
+            };
+        }
+        assert_eq!(packet.depth(), 2001);
+        assert_eq!(packet.eval_iter(), 42);
+    }
+
+    #[test]
+    fn overflow() {
+        let literal = |value| Packet {
+            version: 0,
+            operator: Operator::Literal(value),
+        };
+        let packet = Packet {
+            version: 0,
+            operator: Operator::Product(vec![literal(1 << 40), literal(1 << 30)]),
+        };
+        assert!(matches!(packet.eval_checked(), Err(Error::Overflow)));
+
+        let packet = Packet {
+            version: 0,
+            operator: Operator::Sum(vec![literal(u64::MAX), literal(1)]),
+        };
+        assert!(matches!(packet.eval_checked(), Err(Error::Overflow)));
+
+        let packet = Packet {
+            version: 0,
+            operator: Operator::Product(vec![literal(1 << 40), literal(1 << 20)]),
+        };
+        assert_eq!(packet.eval_checked().unwrap(), 1 << 60);
+    }
 }
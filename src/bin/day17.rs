@@ -1,7 +1,46 @@
+use advent_of_code_2021::{Answer, Input, Solution};
+use itertools::Itertools;
 use std::error;
 use std::ops::RangeInclusive;
+use std::str::FromStr;
 
-type Area = (RangeInclusive<isize>, RangeInclusive<isize>);
+pub(crate) type Area = (RangeInclusive<isize>, RangeInclusive<isize>);
+
+/// Velocity, steps needed, last position and max height of a hitting shot
+type Shot = ((isize, isize), usize, (isize, isize), isize);
+
+/// Parse target area description, e.g. `target area: x=20..30, y=-10..-5`
+fn parse_target_area(s: &str) -> Result<Area, Box<dyn error::Error>> {
+    fn parse_range(s: &str, axis: &str) -> Result<RangeInclusive<isize>, Box<dyn error::Error>> {
+        let (from, to) = s
+            .trim()
+            .strip_prefix(axis)
+            .and_then(|s| s.strip_prefix('='))
+            .and_then(|s| s.split_once(".."))
+            .ok_or("Invalid target area range")?;
+        let (from, to): (isize, isize) = (from.trim().parse()?, to.trim().parse()?);
+        Ok(from.min(to)..=from.max(to))
+    }
+
+    let (x, y) = s
+        .trim()
+        .strip_prefix("target area:")
+        .and_then(|s| s.split_once(','))
+        .ok_or("Invalid target area")?;
+    Ok((parse_range(x, "x")?, parse_range(y, "y")?))
+}
+
+/// Target area parsed from puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TargetArea(pub(crate) Area);
+
+impl FromStr for TargetArea {
+    type Err = Box<dyn error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_target_area(s).map(Self)
+    }
+}
 
 /// Result of a probe
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +50,26 @@ enum ProbeResult {
     Uncertain,
 }
 
+/// Outcome of firing a probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FireResult {
+    /// Steps needed, last position and max height of a hitting probe
+    Hit(usize, (isize, isize), isize),
+    Miss,
+    /// Neither hit nor missed within the step limit
+    Unresolved,
+}
+
+impl FireResult {
+    /// Steps needed, last position and max height if the probe hit
+    fn hit(self) -> Option<(usize, (isize, isize), isize)> {
+        match self {
+            Self::Hit(steps, position, max_y) => Some((steps, position, max_y)),
+            Self::Miss | Self::Unresolved => None,
+        }
+    }
+}
+
 /// Moving probe
 #[derive(Debug)]
 struct Probe {
@@ -40,9 +99,9 @@ impl Probe {
     }
 
     /// Check probe reaching target area
-    ///   Ok(true): inside target area
-    ///   Ok(false): outside target area but moving towards
-    ///   Err(()): outside target area and moving away from it
+    ///   Hit: inside target area
+    ///   Uncertain: outside target area but moving towards
+    ///   Miss: outside target area and moving away from it
     fn check_target(&self, target_area: &Area) -> ProbeResult {
         if target_area.0.contains(&self.position.0) && target_area.1.contains(&self.position.1) {
             ProbeResult::Hit
@@ -56,46 +115,211 @@ impl Probe {
             ProbeResult::Miss
         }
     }
+
+    /// Fire a probe with the given velocity and trace its positions until it
+    /// hits or misses the target area. Since gravity eventually makes every
+    /// probe fall below the target area, the result is always certain after
+    /// enough steps. Also reports whether the probe hit the target area.
+    #[allow(dead_code)]
+    fn trajectory(velocity: (isize, isize), target_area: &Area) -> (Vec<(isize, isize)>, bool) {
+        let mut probe = Self::new(velocity);
+        let mut positions = Vec::new();
+        loop {
+            match probe.check_target(target_area) {
+                ProbeResult::Hit => return (positions, true),
+                ProbeResult::Miss => return (positions, false),
+                ProbeResult::Uncertain => {
+                    Self::step(&mut probe);
+                    positions.push(probe.position);
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for Probe {
+    type Item = (isize, isize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.step();
+        Some(self.position)
+    }
+}
+
+/// Number of steps after which a probe with the given velocity has certainly
+/// hit or missed the target area. Drag stops horizontal movement after `|vx|`
+/// steps, and a probe shot up with `vy` is back at launch height after
+/// `2 * vy + 1` steps and falls by at least one per step from there on.
+fn max_steps(velocity: (isize, isize), target_area: &Area) -> usize {
+    let (vx, vy) = velocity;
+    let fall = target_area.1.start().min(&0).unsigned_abs();
+    vx.unsigned_abs()
+        .max(2 * vy.max(0).unsigned_abs() + 1 + fall)
 }
 
 /// Fire a probe with the given velocity and report steps needed, last position
 /// and max height if it hits
-fn fire(velocity: (isize, isize), target_area: &Area) -> Option<(usize, (isize, isize), isize)> {
+fn fire(velocity: (isize, isize), target_area: &Area) -> FireResult {
     let mut probe = Probe::new(velocity);
-    for i in 0..400 {
-        probe.step();
+    for steps in 0..=max_steps(velocity, target_area) {
         match probe.check_target(target_area) {
-            ProbeResult::Hit => return Some((i + 1, probe.position, probe.max_y)),
-            ProbeResult::Miss => return None,
-            ProbeResult::Uncertain => (),
+            ProbeResult::Hit => return FireResult::Hit(steps, probe.position, probe.max_y),
+            ProbeResult::Miss => return FireResult::Miss,
+            ProbeResult::Uncertain => Probe::step(&mut probe),
         }
     }
-    None
+    FireResult::Unresolved
+}
+
+/// Max height of a probe hitting the target area. A probe shot up with `vy` is
+/// at height `vy` after the first step and passes height 0 again with `-vy - 1`,
+/// so the best shot reaches the top of a target area above or the bottom of a
+/// target area below. A target area at launch height has no maximum, unless
+/// it contains the launch point, which every probe hits right away.
+fn max_height(target_area: &Area) -> Option<isize> {
+    let (y_min, y_max) = (*target_area.1.start(), *target_area.1.end());
+    let vy = if target_area.0.contains(&0) && target_area.1.contains(&0) {
+        0
+    } else if y_max < 0 {
+        -y_min - 1
+    } else if y_min > 0 {
+        y_max
+    } else {
+        return None;
+    };
+    Some(vy * (vy + 1) / 2)
+}
+
+/// Smallest non-negative initial speed that reaches the given distance before
+/// drag (or gravity) stops the probe, i.e. the first triangular number not
+/// less than the distance
+fn min_speed(distance: isize) -> isize {
+    (0..).find(|v| v * (v + 1) / 2 >= distance).unwrap()
+}
+
+/// Ranges of initial velocities to search for probe hits. Horizontally, a probe
+/// needs to be fast enough to reach the near edge before drag stops it, and any
+/// faster probe than the far edge overshoots after the first step. Vertically,
+/// a probe shot up with `vy` comes back down to launch height with `-vy - 1`,
+/// so it must not be faster than that to hit a target area below. A target
+/// area at launch height can be hit with arbitrarily fast probes, so there's
+/// no bounded search space.
+fn search_space(target_area: &Area) -> Option<(RangeInclusive<isize>, RangeInclusive<isize>)> {
+    let (x_min, x_max) = (*target_area.0.start(), *target_area.0.end());
+    let (y_min, y_max) = (*target_area.1.start(), *target_area.1.end());
+    let vx_range = if x_min > 0 {
+        min_speed(x_min)..=x_max
+    } else if x_max < 0 {
+        x_min..=-min_speed(-x_max)
+    } else {
+        x_min..=x_max
+    };
+    let vy_range = if y_max < 0 {
+        y_min..=-y_min - 1
+    } else if y_min > 0 {
+        min_speed(y_min)..=y_max
+    } else {
+        return None;
+    };
+    Some((vx_range, vy_range))
+}
+
+/// Brute-force all shots hitting the target area (sorted by velocity, since
+/// the search space is traversed in ascending order). Returns `None` if
+/// there's no bounded search space
+fn all_hits(target_area: &Area) -> Option<Vec<Shot>> {
+    let (vx_range, vy_range) = search_space(target_area)?;
+    let hits = vx_range
+        .cartesian_product(vy_range)
+        .filter_map(|velocity| {
+            fire(velocity, target_area)
+                .hit()
+                .map(|(n, pos, y)| (velocity, n, pos, y))
+        })
+        .collect();
+    Some(hits)
+}
+
+/// Number of distinct velocities with probe hits
+fn count_hits(target_area: &Area) -> Option<usize> {
+    all_hits(target_area).map(|hits| hits.len())
+}
+
+/// Highest-arcing of the given hits
+fn best_shot(hits: &[Shot]) -> Option<Shot> {
+    hits.iter()
+        .fold(None, |top, &(velocity, n, pos, y)| match top {
+            Some((_velocity, _n, _pos, max_y)) if max_y >= y => top,
+            _ => Some((velocity, n, pos, y)),
+        })
 }
 
 /// Brute-force number of distinct velocities with probe hits and max height
-fn brute_force_hits(target_area: &Area) -> Option<((isize, isize), isize, usize)> {
-    let mut top = None;
-    let mut hits = 0;
-    for vx in -200..200 {
-        for vy in -200..200 {
-            if let Some((_n, _pos, y)) = fire((vx, vy), target_area) {
-                hits += 1;
-                if top.is_none() || matches!(top, Some((_velocity, max_y)) if y > max_y) {
-                    top = Some(((vx, vy), y));
-                }
-            }
-        }
+#[allow(dead_code)]
+pub(crate) fn brute_force_hits(target_area: &Area) -> Option<((isize, isize), isize, usize)> {
+    let hits = all_hits(target_area)?;
+    best_shot(&hits).map(|(velocity, _n, _pos, max_y)| (velocity, max_y, hits.len()))
+}
+
+/// Brute-force number of distinct velocities with probe hits and max height,
+/// searching velocities in parallel
+#[cfg(feature = "parallel")]
+#[allow(dead_code)]
+fn brute_force_hits_parallel(target_area: &Area) -> Option<((isize, isize), isize, usize)> {
+    use rayon::prelude::*;
+    use std::cmp::Reverse;
+
+    let (vx_range, vy_range) = search_space(target_area)?;
+    let hits: Vec<_> = vx_range
+        .into_par_iter()
+        .flat_map(|vx| vy_range.clone().into_par_iter().map(move |vy| (vx, vy)))
+        .filter_map(|velocity| {
+            fire(velocity, target_area)
+                .hit()
+                .map(|(_n, _pos, y)| (velocity, y))
+        })
+        .collect();
+    hits.iter()
+        .max_by_key(|&&(velocity, max_y)| (max_y, Reverse(velocity)))
+        .map(|&(velocity, max_y)| (velocity, max_y, hits.len()))
+}
+
+/// Day 17 solution
+#[allow(dead_code)]
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: usize = 17;
+
+    fn part1(mut input: Input) -> Answer {
+        let TargetArea(target_area) = input.line()?.parse()?;
+        Ok(max_height(&target_area).ok_or("No max height")?.to_string())
+    }
+
+    fn part2(mut input: Input) -> Answer {
+        let TargetArea(target_area) = input.line()?.parse()?;
+        Ok(count_hits(&target_area)
+            .ok_or("Unbounded number of hits")?
+            .to_string())
     }
-    top.map(|(velocity, max_y)| (velocity, max_y, hits))
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
-    const TARGET_AREA: Area = (57..=116, -198..=-148);
+    let TargetArea(target_area) = Input::day(17)?.line()?.parse()?;
+
+    println!(
+        "Max probe height: {}",
+        max_height(&target_area).ok_or("No max height")?
+    );
+
+    let hits = all_hits(&target_area).ok_or("Unbounded number of hits")?;
+    let ((vx, vy), n, (x, y), _max_y) = best_shot(&hits).ok_or("No solution")?;
+    println!(
+        "Best shot with velocity {},{} hits at {},{} after {} steps",
+        vx, vy, x, y, n
+    );
 
-    let ((_vx, _vy), max_y, hits) = brute_force_hits(&TARGET_AREA).ok_or("No solution")?;
-    println!("Max probe height: {}", max_y);
-    println!("Number of initial velocities with hits: {}", hits);
+    println!("Number of initial velocities with hits: {}", hits.len());
 
     Ok(())
 }
@@ -106,33 +330,264 @@ mod tests {
 
     const TARGET_AREA: Area = (20..=30, -10..=-5);
 
+    /// Initial velocities of all shots hitting the target area
+    fn velocities(target_area: &Area) -> Vec<(isize, isize)> {
+        all_hits(target_area)
+            .unwrap()
+            .into_iter()
+            .map(|(velocity, _n, _pos, _max_y)| velocity)
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_target_area("target area: x=20..30, y=-10..-5").unwrap(),
+            TARGET_AREA
+        );
+        assert_eq!(
+            parse_target_area("target area: x=57..116, y=-198..-148").unwrap(),
+            (57..=116, -198..=-148)
+        );
+        assert_eq!(
+            parse_target_area("  target area: x=30..20, y=-5..-10\n").unwrap(),
+            TARGET_AREA
+        );
+        assert!(parse_target_area("target area: x=20..30").is_err());
+        assert!(parse_target_area("target area: x=20..30, z=-10..-5").is_err());
+        assert!(parse_target_area("target area: x=20..a, y=-10..-5").is_err());
+    }
+
+    #[test]
+    fn parse_target_area_newtype() {
+        let target_area: TargetArea = "target area: x=20..30, y=-10..-5".parse().unwrap();
+        assert_eq!(target_area, TargetArea((20..=30, -10..=-5)));
+        let target_area: TargetArea = " target area: x=57..116, y=-198..-148 ".parse().unwrap();
+        assert_eq!(target_area.0, (57..=116, -198..=-148));
+        assert!("target area: x=20..30".parse::<TargetArea>().is_err());
+    }
+
     #[test]
     fn part_1a() {
-        assert_eq!(fire((7, 2), &TARGET_AREA), Some((7, (28, -7), 3)));
+        assert_eq!(fire((7, 2), &TARGET_AREA), FireResult::Hit(7, (28, -7), 3));
     }
 
     #[test]
     fn part_1b() {
-        assert_eq!(fire((6, 3), &TARGET_AREA), Some((9, (21, -9), 6)));
+        assert_eq!(fire((6, 3), &TARGET_AREA), FireResult::Hit(9, (21, -9), 6));
     }
 
     #[test]
     fn part_1c() {
-        assert_eq!(fire((9, 0), &TARGET_AREA), Some((4, (30, -6), 0)));
+        assert_eq!(fire((9, 0), &TARGET_AREA), FireResult::Hit(4, (30, -6), 0));
     }
 
     #[test]
     fn part_1d() {
-        assert_eq!(fire((17, -4), &TARGET_AREA), None);
+        assert_eq!(fire((17, -4), &TARGET_AREA), FireResult::Miss);
     }
 
     #[test]
     fn part_1e() {
-        assert_eq!(fire((6, 9), &TARGET_AREA), Some((20, (21, -10), 45)));
+        assert_eq!(
+            fire((6, 9), &TARGET_AREA),
+            FireResult::Hit(20, (21, -10), 45)
+        );
+    }
+
+    #[test]
+    fn part_1_analytic() {
+        assert_eq!(max_height(&TARGET_AREA), Some(45));
+        let (_velocity, max_y, _hits) = brute_force_hits(&TARGET_AREA).unwrap();
+        assert_eq!(max_height(&TARGET_AREA), Some(max_y));
+
+        let target_area = (57..=116, -198..=-148);
+        let (_velocity, max_y, _hits) = brute_force_hits(&target_area).unwrap();
+        assert_eq!(max_height(&target_area), Some(max_y));
+
+        // Needs vy=499, which is outside of the brute-force search space
+        assert_eq!(max_height(&(1000..=1100, -500..=-400)), Some(124750));
+    }
+
+    #[test]
+    fn target_above() {
+        let target_area = (20..=30, 5..=10);
+        assert_eq!(
+            fire((6, 10), &target_area),
+            FireResult::Hit(20, (21, 10), 55)
+        );
+        assert_eq!(fire((6, 11), &target_area), FireResult::Miss);
+        assert_eq!(fire((7, -1), &target_area), FireResult::Miss);
+        assert_eq!(max_height(&target_area), Some(55));
+        let ((_vx, vy), max_y, _hits) = brute_force_hits(&target_area).unwrap();
+        assert_eq!((vy, max_y), (10, 55));
+
+        assert_eq!(max_height(&(20..=30, -5..=10)), None);
+        assert_eq!(count_hits(&(20..=30, -5..=10)), None);
+        assert_eq!(brute_force_hits(&(20..=30, -5..=10)), None);
+    }
+
+    #[test]
+    fn probe_positions() {
+        let positions: Vec<_> = Probe::new((7, 2)).take(3).collect();
+        assert_eq!(positions, [(7, 2), (13, 3), (18, 3)]);
+
+        let positions: Vec<_> = Probe::new((7, 2)).take_while(|&(_x, y)| y >= 0).collect();
+        assert_eq!(positions, [(7, 2), (13, 3), (18, 3), (22, 2), (25, 0)]);
+    }
+
+    #[test]
+    fn target_at_origin() {
+        let target_area = (-5..=5, -5..=5);
+        assert_eq!(fire((0, 0), &target_area), FireResult::Hit(0, (0, 0), 0));
+        assert_eq!(fire((10, 10), &target_area), FireResult::Hit(0, (0, 0), 0));
+        assert_eq!(Probe::trajectory((0, 0), &target_area), (vec![], true));
+        assert_eq!(max_height(&target_area), Some(0));
+        // Every probe hits right away, no matter how fast
+        assert_eq!(all_hits(&target_area), None);
+    }
+
+    #[test]
+    fn trace() {
+        let (positions, hit) = Probe::trajectory((7, 2), &TARGET_AREA);
+        assert!(hit);
+        assert_eq!(positions.len(), 7);
+        assert_eq!(positions.iter().map(|&(_x, y)| y).max(), Some(3));
+        assert_eq!(
+            positions,
+            [
+                (7, 2),
+                (13, 3),
+                (18, 3),
+                (22, 2),
+                (25, 0),
+                (27, -3),
+                (28, -7)
+            ]
+        );
+        let &(x, y) = positions.last().unwrap();
+        assert!(TARGET_AREA.0.contains(&x) && TARGET_AREA.1.contains(&y));
+
+        let (positions, hit) = Probe::trajectory((17, -4), &TARGET_AREA);
+        assert!(!hit);
+        assert_eq!(positions, [(17, -4), (33, -9)]);
+    }
+
+    #[test]
+    fn high_shot() {
+        let target_area = (1000..=1100, -500..=-400);
+        assert_eq!(
+            fire((45, 499), &target_area),
+            FireResult::Hit(1000, (1035, -500), 124750)
+        );
+        assert_eq!(fire((45, 500), &target_area), FireResult::Miss);
+    }
+
+    #[test]
+    fn resolved() {
+        let (vx_range, vy_range) = search_space(&TARGET_AREA).unwrap();
+        for velocity in vx_range.cartesian_product(vy_range) {
+            assert_ne!(fire(velocity, &TARGET_AREA), FireResult::Unresolved);
+        }
+        let target_area = (1000..=1100, -500..=-400);
+        assert_eq!(max_steps((45, 499), &target_area), 1499);
+        assert_eq!(max_steps((-45, -1), &target_area), 501);
+        assert_eq!(FireResult::Unresolved.hit(), None);
+    }
+
+    #[test]
+    fn search_space_bounds() {
+        assert_eq!(search_space(&TARGET_AREA), Some((6..=30, -10..=9)));
+        assert_eq!(
+            search_space(&(-30..=-20, -10..=-5)),
+            Some((-30..=-6, -10..=9))
+        );
+        assert_eq!(search_space(&(20..=30, 5..=10)), Some((6..=30, 3..=10)));
+        assert_eq!(search_space(&(-5..=5, -5..=5)), None);
+        assert_eq!(search_space(&(20..=30, 0..=10)), None);
+    }
+
+    #[test]
+    fn far_target() {
+        // Needs velocities outside of the former -200..200 brute-force box
+        let target_area = (250..=260, -300..=-290);
+        let (velocity, max_y, hits) = brute_force_hits(&target_area).unwrap();
+        assert_eq!(max_y, 44850);
+        assert_eq!(max_height(&target_area), Some(max_y));
+        assert_eq!(velocity.1, 299);
+        assert!(hits > 0);
+        assert!(velocities(&target_area).contains(&(260, -300)));
+    }
+
+    #[test]
+    fn part_1_best_shot() {
+        assert_eq!(
+            best_shot(&all_hits(&TARGET_AREA).unwrap()),
+            Some(((6, 9), 20, (21, -10), 45))
+        );
     }
 
     #[test]
     fn part_2() {
         assert_eq!(brute_force_hits(&TARGET_AREA), Some(((6, 9), 45, 112)));
     }
+
+    #[test]
+    fn wide_shot() {
+        // Needs vx>200, which was outside of the former brute-force search space
+        let target_area = (300..=310, -10..=-5);
+        let velocities = velocities(&target_area);
+        assert!(velocities.contains(&(305, -7)));
+        assert_eq!(
+            brute_force_hits(&target_area),
+            Some(((25, 9), 45, velocities.len()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn part_2_parallel() {
+        assert_eq!(
+            brute_force_hits_parallel(&TARGET_AREA),
+            brute_force_hits(&TARGET_AREA)
+        );
+        let target_area = (20..=30, 5..=10);
+        assert_eq!(
+            brute_force_hits_parallel(&target_area),
+            brute_force_hits(&target_area)
+        );
+    }
+
+    #[test]
+    fn part_2_count() {
+        assert_eq!(count_hits(&TARGET_AREA), Some(112));
+
+        // Target area mirrored to the left of the launch point
+        let target_area = (-30..=-20, -10..=-5);
+        assert_eq!(count_hits(&target_area), Some(112));
+        assert!(velocities(&target_area).contains(&(-6, 9)));
+    }
+
+    #[test]
+    fn all_shots() {
+        let hits = all_hits(&TARGET_AREA).unwrap();
+        assert!(hits.contains(&((7, 2), 7, (28, -7), 3)));
+        assert!(hits.contains(&((6, 9), 20, (21, -10), 45)));
+        assert!(hits.iter().all(
+            |&(velocity, n, pos, y)| fire(velocity, &TARGET_AREA) == FireResult::Hit(n, pos, y)
+        ));
+    }
+
+    #[test]
+    fn part_2_velocities() {
+        let velocities = velocities(&TARGET_AREA);
+        assert_eq!(velocities.len(), 112);
+        assert!(velocities.contains(&(6, 9)));
+        assert!(velocities.contains(&(7, 2)));
+        assert!(velocities.contains(&(30, -10)));
+        assert!(!velocities.contains(&(17, -4)));
+        assert!(velocities.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(velocities[0], (6, 0));
+        assert_eq!(velocities[111], (30, -5));
+    }
 }
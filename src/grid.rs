@@ -0,0 +1,248 @@
+//! Advent of Code: two-dimensional grids
+
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Grid error
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GridError {
+    #[error("Invalid digit `{0}` in row {1}")]
+    InvalidDigit(char, usize),
+    #[error("Row {0} has a different length than the first row")]
+    RaggedRow(usize),
+}
+
+/// Relative positions of horizontally and vertically adjacent cells
+const DELTAS4: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Relative positions of horizontally, vertically and diagonally adjacent cells
+const DELTAS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Two-dimensional grid of cells, addressed by `(x, y)` positions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Create grid of the given size with all cells set to the given value
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self {
+            cells: vec![value; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> TryFrom<Vec<Vec<T>>> for Grid<T> {
+    type Error = GridError;
+
+    fn try_from(rows: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(GridError::RaggedRow(y + 1));
+            }
+            cells.extend(row);
+        }
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+}
+
+impl FromStr for Grid<u8> {
+    type Err = GridError;
+
+    /// Parse grid of single digit numbers, one row per line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<Vec<u8>> = s
+            .lines()
+            .map(str::trim_end)
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .map(|ch| {
+                        ch.to_digit(10)
+                            .and_then(|n| u8::try_from(n).ok())
+                            .ok_or(GridError::InvalidDigit(ch, y + 1))
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Self::try_from(rows)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Width of grid
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height of grid
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get cell at the given position if exists
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.cells[y * self.width + x])
+    }
+
+    /// Get mutable cell at the given position if exists
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        (x < self.width && y < self.height).then(|| &mut self.cells[y * self.width + x])
+    }
+
+    /// All positions of the grid, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Horizontally and vertically adjacent positions that are within the grid
+    pub fn neighbors4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.adjacent(x, y, &DELTAS4)
+    }
+
+    /// Horizontally, vertically and diagonally adjacent positions that are
+    /// within the grid
+    pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.adjacent(x, y, &DELTAS8)
+    }
+
+    /// Positions with the given relative offsets that are within the grid
+    fn adjacent(
+        &self,
+        x: usize,
+        y: usize,
+        deltas: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        deltas
+            .iter()
+            .filter_map(move |(dx, dy)| {
+                Some((x.checked_add_signed(*dx)?, y.checked_add_signed(*dy)?))
+            })
+            .filter(move |(x, y)| *x < width && *y < height)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        self.get(x, y).expect("Grid position out of bounds")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        self.get_mut(x, y).expect("Grid position out of bounds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<u8> {
+        "123\n456\n789\n".parse().unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let grid = grid();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.get(0, 0), Some(&1));
+        assert_eq!(grid.get(2, 1), Some(&6));
+        assert_eq!(grid.get(1, 2), Some(&8));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 3), None);
+        assert_eq!(grid[(2, 2)], 9);
+
+        assert_eq!(
+            "12 \n34\t\n".parse::<Grid<u8>>(),
+            Ok(Grid::try_from(vec![vec![1, 2], vec![3, 4]]).unwrap())
+        );
+        assert_eq!(
+            "12\n3x".parse::<Grid<u8>>(),
+            Err(GridError::InvalidDigit('x', 2))
+        );
+        assert_eq!("12\n345".parse::<Grid<u8>>(), Err(GridError::RaggedRow(2)));
+        assert_eq!("".parse::<Grid<u8>>().map(|g| g.positions().count()), Ok(0));
+    }
+
+    #[test]
+    fn modify() {
+        let mut grid = Grid::new(2, 3, 0);
+        *grid.get_mut(1, 2).unwrap() = 5;
+        grid[(0, 1)] = 3;
+        assert!(grid.get_mut(2, 0).is_none());
+        assert_eq!(
+            grid,
+            Grid::try_from(vec![vec![0, 0], vec![3, 0], vec![0, 5]]).unwrap()
+        );
+    }
+
+    #[test]
+    fn positions() {
+        let grid = Grid::new(2, 2, ());
+        assert_eq!(
+            grid.positions().collect::<Vec<_>>(),
+            [(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn neighbors4() {
+        let grid = grid();
+        assert_eq!(grid.neighbors4(0, 0).collect::<Vec<_>>(), [(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbors4(2, 0).collect::<Vec<_>>(), [(1, 0), (2, 1)]);
+        assert_eq!(grid.neighbors4(0, 2).collect::<Vec<_>>(), [(1, 2), (0, 1)]);
+        assert_eq!(grid.neighbors4(2, 2).collect::<Vec<_>>(), [(1, 2), (2, 1)]);
+        assert_eq!(
+            grid.neighbors4(1, 0).collect::<Vec<_>>(),
+            [(0, 0), (2, 0), (1, 1)]
+        );
+        assert_eq!(
+            grid.neighbors4(0, 1).collect::<Vec<_>>(),
+            [(1, 1), (0, 0), (0, 2)]
+        );
+        assert_eq!(grid.neighbors4(1, 1).count(), 4);
+        assert_eq!(Grid::new(1, 1, 0).neighbors4(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn neighbors8() {
+        let grid = grid();
+        assert_eq!(
+            grid.neighbors8(0, 0).collect::<Vec<_>>(),
+            [(1, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(grid.neighbors8(1, 0).count(), 5);
+        assert_eq!(grid.neighbors8(1, 1).count(), 8);
+        assert_eq!(grid.neighbors8(2, 2).count(), 3);
+    }
+}
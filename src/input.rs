@@ -4,34 +4,91 @@
 
 use itertools::Itertools;
 use std::error;
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Cursor, Read};
 use std::path::PathBuf;
 use std::str::FromStr;
+use thiserror::Error;
 
 /// Path to puzzle input files
 const INPUT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/input");
 
+/// Puzzle input error
+#[derive(Debug, Error)]
+pub enum InputError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("line {line}: {source}")]
+    Parse {
+        line: usize,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+    #[error("Input exhausted")]
+    Exhausted,
+}
+
 /// Puzzle input
-#[derive(Debug)]
 pub struct Input {
-    reader: BufReader<File>,
+    reader: BufReader<Box<dyn Read>>,
+    peeked: Option<String>,
+    lines_read: usize,
+}
+
+impl fmt::Debug for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Input")
+            .field("peeked", &self.peeked)
+            .field("lines_read", &self.lines_read)
+            .finish_non_exhaustive()
+    }
 }
 
 // Constructors
 impl Input {
     /// Open puzzle input for the given day
     pub fn day(day: usize) -> io::Result<Self> {
-        Self::open(&format!("day{:02}", day))
+        Self::open(&format!("day{day:02}"))
     }
 
-    /// Open puzzle input with the given name
+    /// Open puzzle input with the given name (falls back to a gzip-compressed
+    /// file if the `gzip` feature is enabled)
     pub fn open(name: &str) -> io::Result<Self> {
         let mut filename: PathBuf = INPUT_PATH.into();
         filename.push(name);
         filename.set_extension("txt");
-        let reader = BufReader::new(File::open(filename)?);
-        Ok(Input { reader })
+        #[cfg(feature = "gzip")]
+        if !filename.exists() {
+            let gz_filename = filename.with_extension("txt.gz");
+            if gz_filename.exists() {
+                let decoder = flate2::read::GzDecoder::new(File::open(gz_filename)?);
+                return Ok(Self::from_reader(decoder));
+            }
+        }
+        Ok(Self::from_reader(File::open(filename)?))
+    }
+
+    /// Use puzzle input from the given reader
+    pub fn from_reader(reader: impl Read + 'static) -> Self {
+        let reader: Box<dyn Read> = Box::new(reader);
+        Input {
+            reader: BufReader::new(reader),
+            peeked: None,
+            lines_read: 0,
+        }
+    }
+
+    /// Use puzzle input from the given string
+    #[allow(clippy::should_implement_trait)]
+    #[must_use]
+    pub fn from_str(s: &str) -> Self {
+        Self::from_reader(Cursor::new(s.to_owned().into_bytes()))
+    }
+
+    /// Use puzzle input from stdin
+    #[must_use]
+    pub fn stdin() -> Self {
+        Self::from_reader(io::stdin().lock())
     }
 }
 
@@ -39,78 +96,252 @@ impl Input {
 impl Input {
     /// Iterator over lines of this input
     pub fn lines(self) -> impl Iterator<Item = io::Result<String>> {
-        self.reader.lines()
+        self.peeked.into_iter().map(Ok).chain(self.reader.lines())
+    }
+
+    /// Iterator over lines of this input with trailing whitespace removed
+    pub fn lines_trimmed(self) -> impl Iterator<Item = io::Result<String>> {
+        self.lines().map(|line| {
+            line.map(|mut s| {
+                s.truncate(s.trim_end().len());
+                s
+            })
+        })
     }
 
     /// Iterator over parsed lines of this input
-    pub fn parsed_lines<T>(self) -> impl Iterator<Item = io::Result<T>>
+    pub fn parsed_lines<T>(self) -> impl Iterator<Item = Result<T, InputError>>
     where
         T: FromStr,
         T::Err: error::Error + Send + Sync + 'static,
     {
-        self.lines().map(|line| {
-            line.and_then(|s| {
-                s.parse()
-                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        let offset = self.lines_read;
+        self.lines().enumerate().map(move |(i, line)| {
+            line?.parse().map_err(|e| InputError::Parse {
+                line: offset + i + 1,
+                source: Box::new(e),
             })
         })
     }
 
+    /// Iterator over parsed whitespace-separated tokens of this input
+    pub fn tokens<T>(self) -> impl Iterator<Item = Result<T, InputError>>
+    where
+        T: FromStr,
+        T::Err: error::Error + Send + Sync + 'static,
+    {
+        let offset = self.lines_read;
+        self.lines()
+            .enumerate()
+            .flat_map(|(i, line)| match line {
+                Ok(line) => line
+                    .split_whitespace()
+                    .map(|s| Ok((i, s.to_owned())))
+                    .collect(),
+                Err(e) => vec![Err(InputError::from(e))],
+            })
+            .map(move |token| {
+                token.and_then(|(i, s)| {
+                    s.parse().map_err(|e| InputError::Parse {
+                        line: offset + i + 1,
+                        source: Box::new(e),
+                    })
+                })
+            })
+    }
+
+    /// Parse grid of digits
+    pub fn digit_grid(self) -> Result<Vec<Vec<u8>>, InputError> {
+        let offset = self.lines_read;
+        let mut grid: Vec<Vec<u8>> = Vec::new();
+        for line in self.lines_trimmed() {
+            let line_no = offset + grid.len() + 1;
+            let row: Vec<u8> = line?
+                .chars()
+                .map(|ch| {
+                    ch.to_digit(10)
+                        .and_then(|n| u8::try_from(n).ok())
+                        .ok_or_else(|| InputError::Parse {
+                            line: line_no,
+                            source: format!("Invalid digit `{ch}`").into(),
+                        })
+                })
+                .try_collect()?;
+            if let Some(first) = grid.first() {
+                if row.len() != first.len() {
+                    return Err(InputError::Parse {
+                        line: line_no,
+                        source: format!("Row width {}, expected {}", row.len(), first.len()).into(),
+                    });
+                }
+            }
+            grid.push(row);
+        }
+        Ok(grid)
+    }
+
     /// Iterator over blocks of this input
     pub fn blocks(self) -> impl Iterator<Item = io::Result<Vec<String>>> {
-        fn is_blank_line(line: &io::Result<String>) -> bool {
-            line.as_ref().map(|s| s.trim().is_empty()).unwrap_or(false)
-        }
-        fn is_not_blank_line(line: &io::Result<String>) -> bool {
-            !is_blank_line(line)
-        }
+        blocks_of(self.lines())
+    }
 
-        self.reader.lines().batching(|lines| {
-            let block: io::Result<Vec<_>> = lines
-                .skip_while(is_blank_line)
-                .take_while(is_not_blank_line)
-                .try_collect();
-            match block {
-                Ok(ref lines) if !lines.is_empty() => Some(block),
-                _ => None,
-            }
+    /// Iterator over parsed blocks of this input
+    pub fn blocks_parsed<T>(self) -> impl Iterator<Item = Result<T, InputError>>
+    where
+        T: for<'a> TryFrom<&'a [String]>,
+        for<'a> <T as TryFrom<&'a [String]>>::Error: error::Error + Send + Sync + 'static,
+    {
+        let offset = self.lines_read;
+        numbered_blocks_of(self.lines()).map(move |block| {
+            let (i, lines) = block?;
+            T::try_from(&lines[..]).map_err(|e| InputError::Parse {
+                line: offset + i + 1,
+                source: Box::new(e),
+            })
         })
     }
 }
 
 // Consuming partial input
 impl Input {
+    /// Iterator over remaining lines
+    fn rest_lines(&mut self) -> impl Iterator<Item = io::Result<String>> + '_ {
+        self.peeked
+            .take()
+            .into_iter()
+            .map(Ok)
+            .chain(self.reader.by_ref().lines())
+            .inspect(|_| self.lines_read += 1)
+    }
+
+    /// Peek at the next line without consuming it
+    pub fn peek_line(&mut self) -> io::Result<Option<&str>> {
+        if self.peeked.is_none() {
+            self.peeked = self.reader.by_ref().lines().next().transpose()?;
+        }
+        Ok(self.peeked.as_deref())
+    }
+
     /// Read one line
-    pub fn line(&mut self) -> io::Result<String> {
-        self.reader
-            .by_ref()
-            .lines()
-            .next()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Input exhausted"))?
+    pub fn line(&mut self) -> Result<String, InputError> {
+        Ok(self.rest_lines().next().ok_or(InputError::Exhausted)??)
     }
 
     /// Read and parse one line
-    pub fn parse_line<T>(&mut self) -> io::Result<T>
+    pub fn parse_line<T>(&mut self) -> Result<T, InputError>
+    where
+        T: FromStr,
+        T::Err: error::Error + Send + Sync + 'static,
+    {
+        let line = self.line()?;
+        line.parse().map_err(|e| InputError::Parse {
+            line: self.lines_read,
+            source: Box::new(e),
+        })
+    }
+
+    /// Iterator over remaining blocks
+    pub fn rest_blocks(&mut self) -> impl Iterator<Item = io::Result<Vec<String>>> + '_ {
+        blocks_of(self.rest_lines())
+    }
+
+    /// Read one line and parse its comma-separated values
+    pub fn parsed_csv<T>(&mut self) -> Result<Vec<T>, InputError>
     where
         T: FromStr,
         T::Err: error::Error + Send + Sync + 'static,
     {
         self.line()?
-            .parse()
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .split(',')
+            .map(|s| {
+                s.trim().parse().map_err(|e| InputError::Parse {
+                    line: self.lines_read,
+                    source: Box::new(e),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Group lines into blocks that are separated by blank lines
+fn blocks_of(
+    lines: impl Iterator<Item = io::Result<String>>,
+) -> impl Iterator<Item = io::Result<Vec<String>>> {
+    numbered_blocks_of(lines).map(|block| block.map(|(_i, lines)| lines))
+}
+
+/// Group lines into blocks that are separated by blank lines, together with
+/// the index of the first line of each block
+fn numbered_blocks_of(
+    lines: impl Iterator<Item = io::Result<String>>,
+) -> impl Iterator<Item = io::Result<(usize, Vec<String>)>> {
+    fn is_blank_line((_i, line): &(usize, io::Result<String>)) -> bool {
+        line.as_ref().is_ok_and(|s| s.trim().is_empty())
+    }
+    fn is_not_blank_line(line: &(usize, io::Result<String>)) -> bool {
+        !is_blank_line(line)
     }
+
+    lines.enumerate().batching(|lines| {
+        let mut block = lines
+            .skip_while(is_blank_line)
+            .take_while(is_not_blank_line)
+            .peekable();
+        let (first, _line) = block.peek()?;
+        let first = *first;
+        let block: io::Result<Vec<_>> = block.map(|(_i, line)| line).try_collect();
+        match block {
+            Ok(lines) => Some(Ok((first, lines))),
+            Err(_) => None,
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[derive(Debug, PartialEq, Eq)]
+    struct Sum(u32);
+
+    impl TryFrom<&[String]> for Sum {
+        type Error = std::num::ParseIntError;
+
+        fn try_from(lines: &[String]) -> Result<Self, Self::Error> {
+            lines
+                .iter()
+                .map(|line| line.parse::<u32>())
+                .sum::<Result<_, _>>()
+                .map(Self)
+        }
+    }
+
     #[test]
     fn file() {
         let mut lines = Input::day(1).unwrap().lines();
         let _line = lines.next().unwrap().unwrap();
     }
 
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn gzip_file() {
+        let lines: Vec<_> = Input::open("test-gzip")
+            .unwrap()
+            .lines()
+            .try_collect()
+            .unwrap();
+        assert_eq!(lines, ["11", "22", "33"]);
+    }
+
+    #[test]
+    fn reader() {
+        let lines: Vec<u32> = Input::from_reader(&b"11\n22\n33\n"[..])
+            .parsed_lines()
+            .try_collect()
+            .unwrap();
+        assert_eq!(lines, [11, 22, 33]);
+    }
+
     #[test]
     fn lines() {
         let lines: Vec<_> = Input::open("test-numbers")
@@ -141,6 +372,98 @@ mod tests {
         assert_eq!(lines[4], 55);
     }
 
+    #[test]
+    fn lines_trimmed() {
+        let lines: Vec<_> = Input::open("test-digits-padded")
+            .unwrap()
+            .lines_trimmed()
+            .try_collect()
+            .unwrap();
+        assert_eq!(lines, ["123", "456", "789"]);
+
+        let padded = Input::open("test-digits-padded")
+            .unwrap()
+            .digit_grid()
+            .unwrap();
+        let clean = Input::from_str("123\n456\n789").digit_grid().unwrap();
+        assert_eq!(padded, clean);
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens: Vec<u32> = Input::from_str("1 2\n 3\t4 ")
+            .tokens()
+            .try_collect()
+            .unwrap();
+        assert_eq!(tokens, [1, 2, 3, 4]);
+
+        let tokens: Vec<u32> = Input::from_str("\n\n  5   6\n\n7\n\n")
+            .tokens()
+            .try_collect()
+            .unwrap();
+        assert_eq!(tokens, [5, 6, 7]);
+
+        let tokens: Result<Vec<u32>, _> = Input::from_str("1 2\n3 x").tokens().try_collect();
+        let err = tokens.unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 2, .. }));
+        assert_eq!(err.to_string(), "line 2: invalid digit found in string");
+    }
+
+    #[test]
+    fn digit_grid() {
+        let grid = Input::from_str("123\n456\n").digit_grid().unwrap();
+        assert_eq!(grid, [[1, 2, 3], [4, 5, 6]]);
+
+        let err = Input::from_str("123\n4x6\n").digit_grid().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 2, .. }));
+        assert_eq!(err.to_string(), "line 2: Invalid digit `x`");
+
+        let err = Input::from_str("123\n45\n").digit_grid().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 2, .. }));
+        assert_eq!(err.to_string(), "line 2: Row width 2, expected 3");
+
+        let mut input = Input::from_str("xx\n12\n3\n");
+        input.line().unwrap();
+        let err = input.digit_grid().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 3, .. }));
+    }
+
+    #[test]
+    fn parsed_lines_error() {
+        let mut lines = Input::from_str("11\n22\nxx\n44").parsed_lines::<u32>();
+        assert_eq!(lines.next().unwrap().unwrap(), 11);
+        assert_eq!(lines.next().unwrap().unwrap(), 22);
+        let err = lines.next().unwrap().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 3, .. }));
+        assert_eq!(err.to_string(), "line 3: invalid digit found in string");
+        assert_eq!(lines.next().unwrap().unwrap(), 44);
+    }
+
+    #[test]
+    fn input_error() {
+        let mut input = Input::from_str("11\nxx\n33");
+        assert_eq!(input.parse_line::<u32>().unwrap(), 11);
+        let err = input.parse_line::<u32>().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 2, .. }));
+        assert_eq!(err.to_string(), "line 2: invalid digit found in string");
+        let lines: Vec<u32> = input.parsed_lines().try_collect().unwrap();
+        assert_eq!(lines, [33]);
+
+        let mut input = Input::from_str("11\nxx");
+        assert_eq!(input.line().unwrap(), "11");
+        let err = input.parsed_lines::<u32>().next().unwrap().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 2, .. }));
+
+        let mut input = Input::from_str("");
+        let err = input.parse_line::<u32>().unwrap_err();
+        assert!(matches!(err, InputError::Exhausted));
+        assert_eq!(err.to_string(), "Input exhausted");
+
+        let err = InputError::from(io::Error::new(io::ErrorKind::NotFound, "not found"));
+        assert!(matches!(err, InputError::Io(ref e) if e.kind() == io::ErrorKind::NotFound));
+        assert_eq!(err.to_string(), "not found");
+    }
+
     #[test]
     fn blocks() {
         let blocks: Vec<_> = Input::open("test-blocks")
@@ -160,6 +483,44 @@ mod tests {
         assert_eq!(blocks[2][1], "66");
     }
 
+    #[test]
+    fn string() {
+        let lines: Vec<u32> = Input::from_str("11\n22\n33")
+            .parsed_lines()
+            .try_collect()
+            .unwrap();
+        assert_eq!(lines, [11, 22, 33]);
+    }
+
+    #[test]
+    fn string_blocks() {
+        let blocks: Vec<_> = Input::from_str("\n11\n22\n\n\n33\n\n44\n55\n")
+            .blocks()
+            .try_collect()
+            .unwrap();
+        assert_eq!(blocks, [vec!["11", "22"], vec!["33"], vec!["44", "55"]]);
+
+        let blocks: Vec<_> = Input::from_str("").blocks().try_collect().unwrap();
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    fn blocks_parsed() {
+        let sums: Vec<Sum> = Input::open("test-blocks")
+            .unwrap()
+            .blocks_parsed()
+            .try_collect()
+            .unwrap();
+        assert_eq!(sums, [Sum(33), Sum(77), Sum(121)]);
+
+        let mut sums = Input::from_str("1\n2\n\n3\nx\n\n4").blocks_parsed::<Sum>();
+        assert_eq!(sums.next().unwrap().unwrap(), Sum(3));
+        let err = sums.next().unwrap().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 4, .. }));
+        assert_eq!(sums.next().unwrap().unwrap(), Sum(4));
+        assert!(sums.next().is_none());
+    }
+
     #[test]
     fn partial_line() {
         let mut input = Input::open("test-numbers").unwrap();
@@ -168,6 +529,56 @@ mod tests {
         assert_eq!(input.parse_line::<u32>().unwrap(), 33);
         assert_eq!(input.line().unwrap(), "44");
         assert_eq!(input.line().unwrap(), "55");
+        assert!(matches!(input.line(), Err(InputError::Exhausted)));
+    }
+
+    #[test]
+    fn partial_csv() {
+        let mut input = Input::from_str("16,1,2,0\n 3, 4 ,5\n1,x\n");
+        assert_eq!(input.parsed_csv::<u32>().unwrap(), [16, 1, 2, 0]);
+        assert_eq!(input.parsed_csv::<u32>().unwrap(), [3, 4, 5]);
+        let err = input.parsed_csv::<u32>().unwrap_err();
+        assert!(matches!(err, InputError::Parse { line: 3, .. }));
+        assert!(matches!(
+            input.parsed_csv::<u32>(),
+            Err(InputError::Exhausted)
+        ));
+    }
+
+    #[test]
+    fn partial_blocks() {
+        let mut input = Input::from_str("NNCB\n\nCH -> B\nHH -> N\n\nCB -> H\n");
+        assert_eq!(input.line().unwrap(), "NNCB");
+        let blocks: Vec<_> = input.rest_blocks().try_collect().unwrap();
+        assert_eq!(blocks, [vec!["CH -> B", "HH -> N"], vec!["CB -> H"]]);
+        assert!(input.line().is_err());
+
+        let mut input = Input::open("test-blocks").unwrap();
+        assert_eq!(input.line().unwrap(), "11");
+        assert_eq!(input.rest_blocks().next().unwrap().unwrap(), ["22"]);
+        assert_eq!(input.line().unwrap(), "33");
+        let blocks: Vec<_> = input.rest_blocks().try_collect().unwrap();
+        assert_eq!(blocks, [vec!["44"], vec!["55", "66"]]);
+    }
+
+    #[test]
+    fn partial_peek() {
+        let mut input = Input::open("test-numbers").unwrap();
+        assert_eq!(input.peek_line().unwrap(), Some("11"));
+        assert_eq!(input.peek_line().unwrap(), Some("11"));
+        assert_eq!(input.line().unwrap(), "11");
+        assert_eq!(input.peek_line().unwrap(), Some("22"));
+        assert_eq!(input.parse_line::<u32>().unwrap(), 22);
+        assert_eq!(input.peek_line().unwrap(), Some("33"));
+        let lines: Vec<_> = input.lines().try_collect().unwrap();
+        assert_eq!(lines, ["33", "44", "55"]);
+
+        let mut input = Input::from_str("11\n\n22\n");
+        assert_eq!(input.line().unwrap(), "11");
+        assert_eq!(input.peek_line().unwrap(), Some(""));
+        assert_eq!(input.rest_blocks().next().unwrap().unwrap(), ["22"]);
+        assert_eq!(input.peek_line().unwrap(), None);
+        assert_eq!(input.peek_line().unwrap(), None);
         assert!(input.line().is_err());
     }
 
@@ -2,5 +2,13 @@
 
 #![warn(clippy::pedantic)]
 
+mod grid;
 mod input;
-pub use input::Input;
+mod pathfind;
+mod point;
+mod solution;
+pub use grid::{Grid, GridError};
+pub use input::{Input, InputError};
+pub use pathfind::{astar, dijkstra};
+pub use point::{ParsePointError, Point};
+pub use solution::{timed, Answer, Solution};
@@ -0,0 +1,207 @@
+//! Advent of Code: path finding
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+/// Best known path to a node
+#[derive(Debug)]
+struct BestPath {
+    cost: usize,
+    from: Option<usize>,
+    done: bool,
+}
+
+/// Find the path with the lowest cost from the start node to a goal node
+/// (Dijkstra algorithm). The successors function yields adjacent nodes of a
+/// node together with the cost of moving there. Returns the nodes of the path,
+/// including start and goal, and its total cost.
+pub fn dijkstra<N, FN, IN, FG>(start: N, successors: FN, is_goal: FG) -> Option<(Vec<N>, usize)>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, usize)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+/// Find the path with the lowest cost from the start node to a goal node
+/// (A* algorithm). Like `dijkstra`, but nodes are visited ordered by their
+/// cost plus the estimated remaining cost given by the heuristic function.
+/// The heuristic must never overestimate the cost of moving to a goal node
+/// and must not decrease by more than the cost of a move.
+pub fn astar<N, FN, IN, FH, FG>(
+    start: N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut is_goal: FG,
+) -> Option<(Vec<N>, usize)>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, usize)>,
+    FH: FnMut(&N) -> usize,
+    FG: FnMut(&N) -> bool,
+{
+    let mut queue = BinaryHeap::from([Reverse((heuristic(&start), 0))]);
+    // Nodes are kept in the order of discovery and referred to by index
+    let mut nodes = vec![start.clone()];
+    let mut indices = HashMap::from([(start, 0)]);
+    let mut bestpaths = vec![BestPath {
+        cost: 0,
+        from: None,
+        done: false,
+    }];
+
+    while let Some(Reverse((_estimate, index))) = queue.pop() {
+        // Skip outdated queue entries of already finished nodes
+        if bestpaths[index].done {
+            continue;
+        }
+        bestpaths[index].done = true;
+        let cost = bestpaths[index].cost;
+        if is_goal(&nodes[index]) {
+            let mut path = vec![nodes[index].clone()];
+            let mut index = index;
+            while let Some(from) = bestpaths[index].from {
+                path.push(nodes[from].clone());
+                index = from;
+            }
+            path.reverse();
+            return Some((path, cost));
+        }
+        for (neighbor, step_cost) in successors(&nodes[index]) {
+            let new_cost = cost + step_cost;
+            let neighbor_index = *indices.entry(neighbor).or_insert_with_key(|neighbor| {
+                nodes.push(neighbor.clone());
+                bestpaths.push(BestPath {
+                    cost: usize::MAX,
+                    from: None,
+                    done: false,
+                });
+                nodes.len() - 1
+            });
+            let bestpath = &mut bestpaths[neighbor_index];
+            if !bestpath.done && new_cost < bestpath.cost {
+                bestpath.cost = new_cost;
+                bestpath.from = Some(index);
+                let estimate = new_cost + heuristic(&nodes[neighbor_index]);
+                queue.push(Reverse((estimate, neighbor_index)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Weighted undirected graph: A-B 7, A-C 9, A-F 14, B-C 10, B-D 15, C-D 11,
+    /// C-F 2, D-E 6, E-F 9
+    fn successors(node: char) -> Vec<(char, usize)> {
+        match node {
+            'A' => vec![('B', 7), ('C', 9), ('F', 14)],
+            'B' => vec![('A', 7), ('C', 10), ('D', 15)],
+            'C' => vec![('A', 9), ('B', 10), ('D', 11), ('F', 2)],
+            'D' => vec![('B', 15), ('C', 11), ('E', 6)],
+            'E' => vec![('D', 6), ('F', 9)],
+            'F' => vec![('A', 14), ('C', 2), ('E', 9)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn shortest_path() {
+        assert_eq!(
+            dijkstra('A', |&node| successors(node), |&node| node == 'E'),
+            Some((vec!['A', 'C', 'F', 'E'], 20))
+        );
+        assert_eq!(
+            dijkstra('A', |&node| successors(node), |&node| node == 'D'),
+            Some((vec!['A', 'C', 'D'], 20))
+        );
+        assert_eq!(
+            dijkstra('B', |&node| successors(node), |&node| node == 'F'),
+            Some((vec!['B', 'C', 'F'], 12))
+        );
+    }
+
+    #[test]
+    fn start_is_goal() {
+        assert_eq!(
+            dijkstra('A', |&node| successors(node), |&node| node == 'A'),
+            Some((vec!['A'], 0))
+        );
+    }
+
+    #[test]
+    fn unreachable() {
+        assert_eq!(
+            dijkstra('A', |&node| successors(node), |&node| node == 'X'),
+            None
+        );
+        assert_eq!(
+            dijkstra('X', |&node| successors(node), |&node| node == 'A'),
+            None
+        );
+    }
+
+    #[test]
+    fn grid() {
+        // Unit cost moves right or down on a 5x5 grid
+        let successors = |&(x, y): &(usize, usize)| {
+            [(x + 1, y), (x, y + 1)]
+                .into_iter()
+                .filter(|&(x, y)| x < 5 && y < 5)
+                .map(|pos| (pos, 1))
+        };
+        let (path, cost) = dijkstra((0, 0), successors, |&pos| pos == (4, 4)).unwrap();
+        assert_eq!(cost, 8);
+        assert_eq!(path.len(), 9);
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[8], (4, 4));
+    }
+
+    #[test]
+    fn astar_grid() {
+        // Unit cost moves in all four directions on a 20x20 grid with a wall
+        // at x = 10 that is open at y = 15 only
+        let successors = |&(x, y): &(usize, usize)| {
+            [
+                (x + 1, y),
+                (x, y + 1),
+                (x.wrapping_sub(1), y),
+                (x, y.wrapping_sub(1)),
+            ]
+            .into_iter()
+            .filter(|&(x, y)| x < 20 && y < 20 && (x != 10 || y == 15))
+            .map(|pos| (pos, 1))
+        };
+        let manhattan = |&(x, y): &(usize, usize)| x.abs_diff(19) + y.abs_diff(0);
+        let (path, cost) = astar((0, 0), successors, manhattan, |&pos| pos == (19, 0)).unwrap();
+        assert_eq!(cost, 19 + 15 * 2);
+        assert_eq!(path.len(), cost + 1);
+        assert!(path.contains(&(10, 15)));
+        assert_eq!(
+            dijkstra((0, 0), successors, |&pos| pos == (19, 0)).map(|(_path, cost)| cost),
+            Some(cost)
+        );
+        assert_eq!(
+            astar((0, 0), successors, manhattan, |&pos| pos == (10, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() {
+        for goal in ['A', 'B', 'C', 'D', 'E', 'F', 'X'] {
+            assert_eq!(
+                astar('A', |&node| successors(node), |_| 0, |&node| node == goal),
+                dijkstra('A', |&node| successors(node), |&node| node == goal)
+            );
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Advent of Code: two-dimensional points
+
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Point parse error
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Invalid point")]
+pub struct ParsePointError;
+
+/// Point (or offset between points) on a two-dimensional plane
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl FromStr for Point {
+    type Err = ParsePointError;
+
+    /// Parse point from comma separated coordinates, e.g. `3,4`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = s.split_once(',').ok_or(ParsePointError)?;
+        Ok(Self::new(
+            x.trim().parse().map_err(|_| ParsePointError)?,
+            y.trim().parse().map_err(|_| ParsePointError)?,
+        ))
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: isize) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Point {
+    /// Create a new point with given x and y position
+    #[must_use]
+    pub fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// Manhattan distance to the given point
+    #[must_use]
+    pub fn manhattan_distance(self, other: Self) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Unit step (each coordinate -1, 0 or 1) that moves towards the given
+    /// point horizontally, vertically or diagonally
+    #[must_use]
+    pub fn direction_to(self, other: Self) -> Self {
+        Self::new((other.x - self.x).signum(), (other.y - self.y).signum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("3,4".parse(), Ok(Point::new(3, 4)));
+        assert_eq!(" -3 , 4 ".parse(), Ok(Point::new(-3, 4)));
+        assert_eq!("3".parse::<Point>(), Err(ParsePointError));
+        assert_eq!("3,a".parse::<Point>(), Err(ParsePointError));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(3, -4);
+        let b = Point::new(-1, 2);
+        assert_eq!(a + b, Point::new(2, -2));
+        assert_eq!(a - b, Point::new(4, -6));
+        assert_eq!(b * 3, Point::new(-3, 6));
+        assert_eq!(a + b * -1, a - b);
+        assert_eq!(a + Point::default(), a);
+    }
+
+    #[test]
+    fn manhattan_distance() {
+        let a = Point::new(3, -4);
+        assert_eq!(a.manhattan_distance(a), 0);
+        assert_eq!(a.manhattan_distance(Point::default()), 7);
+        assert_eq!(a.manhattan_distance(Point::new(-1, 2)), 10);
+        assert_eq!(Point::new(-1, 2).manhattan_distance(a), 10);
+    }
+
+    #[test]
+    fn direction_to() {
+        let a = Point::new(2, 2);
+        assert_eq!(a.direction_to(Point::new(2, 1)), Point::new(0, -1));
+        assert_eq!(a.direction_to(Point::new(8, 2)), Point::new(1, 0));
+        assert_eq!(a.direction_to(Point::new(-5, 9)), Point::new(-1, 1));
+        assert_eq!(a.direction_to(a), Point::new(0, 0));
+    }
+}
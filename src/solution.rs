@@ -0,0 +1,67 @@
+//! Advent of Code: puzzle solutions
+
+#![allow(clippy::missing_errors_doc)]
+
+use crate::Input;
+use std::error;
+use std::time::{Duration, Instant};
+
+/// Result of solving a puzzle part
+pub type Answer = Result<String, Box<dyn error::Error>>;
+
+/// Solution to both parts of a day's puzzle
+pub trait Solution {
+    /// Day of the puzzle
+    const DAY: usize;
+
+    /// Solve first part of the puzzle with the given input
+    fn part1(input: Input) -> Answer;
+
+    /// Solve second part of the puzzle with the given input
+    fn part2(input: Input) -> Answer;
+}
+
+/// Solve a puzzle part with the puzzle input of the given day and measure the
+/// elapsed time (including reading the input)
+pub fn timed(
+    day: usize,
+    part: fn(Input) -> Answer,
+) -> Result<(String, Duration), Box<dyn error::Error>> {
+    let start = Instant::now();
+    let answer = part(Input::day(day)?)?;
+    Ok((answer, start.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        const DAY: usize = 0;
+
+        fn part1(input: Input) -> Answer {
+            let numbers: Vec<u32> = input.parsed_lines().try_collect()?;
+            Ok(numbers.iter().sum::<u32>().to_string())
+        }
+
+        fn part2(input: Input) -> Answer {
+            let numbers: Vec<u32> = input.parsed_lines().try_collect()?;
+            Ok(numbers.iter().product::<u32>().to_string())
+        }
+    }
+
+    #[test]
+    fn solution() {
+        assert_eq!(Sum::part1(Input::from_str("2\n3\n4")).unwrap(), "9");
+        assert_eq!(Sum::part2(Input::from_str("2\n3\n4")).unwrap(), "24");
+        assert!(Sum::part1(Input::from_str("2\nx")).is_err());
+    }
+
+    #[test]
+    fn timed_missing_input() {
+        assert!(timed(0, Sum::part1).is_err());
+    }
+}